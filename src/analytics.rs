@@ -0,0 +1,251 @@
+//! Here provide the analytics of the site, the views of the pages routed to
+//! and the reads of posts are sent to the sinks set in `[analytics]`,
+//! nothing is sent if the browser asks not to be tracked (Do Not Track)
+use crate::common::analytics::{Aggregate, AnalyticsConfig, Event};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use wasm_bindgen::{prelude::Closure, JsCast};
+use web_sys::Storage;
+
+/// the key of the counts in `localStorage`
+const LOCAL_KEY: &str = "noer-analytics";
+
+/// where the events go, the sinks other than the built in ones
+/// are added by `register`
+pub trait AnalyticsSink {
+    fn send(&self, event: &Event);
+}
+
+/// log the events, they are shown in development mode
+pub struct ConsoleSink;
+
+impl AnalyticsSink for ConsoleSink {
+    fn send(&self, event: &Event) {
+        log::info!("analytics: {:?}", event);
+    }
+}
+
+/// count the events in `localStorage`, nothing leaves the device
+pub struct LocalSink;
+
+impl AnalyticsSink for LocalSink {
+    fn send(&self, event: &Event) {
+        let storage = match storage() {
+            Some(storage) => storage,
+            None => return,
+        };
+        let mut aggregate = local();
+        aggregate.record(event);
+        if let Ok(s) = serde_json::to_string(&aggregate) {
+            if let Err(e) = storage.set_item(LOCAL_KEY, &s) {
+                log::error!("analytics not saved: {:?}", e);
+            }
+        }
+    }
+}
+
+/// post the events as json to the endpoint by `navigator.sendBeacon`,
+/// so they are delivered even if the page is being closed
+pub struct BeaconSink {
+    pub endpoint: String,
+}
+
+impl AnalyticsSink for BeaconSink {
+    fn send(&self, event: &Event) {
+        let (window, body) = match (web_sys::window(), serde_json::to_string(event)) {
+            (Some(window), Ok(body)) => (window, body),
+            _ => return,
+        };
+        match window
+            .navigator()
+            .send_beacon_with_opt_str(&self.endpoint, Some(&body))
+        {
+            Ok(true) => {}
+            Ok(false) => log::warn!("analytics beacon to {} not queued", self.endpoint),
+            Err(e) => log::error!("analytics beacon to {} failed: {:?}", self.endpoint, e),
+        }
+    }
+}
+
+thread_local! {
+    static SINKS: RefCell<Vec<Box<dyn AnalyticsSink>>> = RefCell::new(vec![]);
+    /// the path viewed last, it is viewed once until another one is routed to
+    static LAST_PATH: RefCell<String> = const { RefCell::new(String::new()) };
+    static COMPLETED_DEPTH: Cell<u8> = Cell::new(AnalyticsConfig::default().completed_depth);
+}
+
+fn storage() -> Option<Storage> {
+    web_sys::window().and_then(|w| w.local_storage().ok().flatten())
+}
+
+/// whether the browser asks not to be tracked
+fn do_not_track() -> bool {
+    web_sys::window()
+        .map(|w| w.navigator().do_not_track() == "1")
+        .unwrap_or(false)
+}
+
+/// set up the sinks of config in place of the ones set before
+pub fn init(config: &AnalyticsConfig) {
+    COMPLETED_DEPTH.with(|e| e.set(config.completed_depth));
+    let sinks = if do_not_track() {
+        log::info!("analytics disabled, the browser asks not to be tracked");
+        vec![]
+    } else {
+        config
+            .sinks
+            .iter()
+            .filter_map(|name| -> Option<Box<dyn AnalyticsSink>> {
+                match name.as_str() {
+                    "console" => Some(Box::new(ConsoleSink)),
+                    "local" => Some(Box::new(LocalSink)),
+                    "beacon" => Some(Box::new(BeaconSink {
+                        endpoint: config.endpoint.clone(),
+                    })),
+                    _ => None,
+                }
+            })
+            .collect()
+    };
+    SINKS.with(|e| *e.borrow_mut() = sinks);
+}
+
+/// add a sink besides the ones of config, unless not tracked
+pub fn register(sink: Box<dyn AnalyticsSink>) {
+    if !do_not_track() {
+        SINKS.with(|e| e.borrow_mut().push(sink));
+    }
+}
+
+/// send the event to all sinks
+pub fn emit(event: &Event) {
+    SINKS.with(|sinks| sinks.borrow().iter().for_each(|e| e.send(event)));
+}
+
+/// the page of route is viewed, eg: "/posts/123/title",
+/// it is not counted again when the page is rendered again
+pub fn page_view(path: &str) {
+    let changed = LAST_PATH.with(|e| {
+        let mut last = e.borrow_mut();
+        let changed = *last != path;
+        *last = path.to_string();
+        changed
+    });
+    if changed {
+        emit(&Event::PageView { path: path.into() });
+    }
+}
+
+/// the counts in `localStorage` by the `local` sink
+pub fn local() -> Aggregate {
+    storage()
+        .and_then(|e| e.get_item(LOCAL_KEY).ok().flatten())
+        .and_then(|e| serde_json::from_str(&e).ok())
+        .unwrap_or_default()
+}
+
+/// how hot the posts are by id on this device, see `Order::Hot`
+pub fn post_scores() -> HashMap<u64, u64> {
+    local().post_scores()
+}
+
+/// the percent of page scrolled to, the bottom of the view
+fn scroll_depth() -> Option<u8> {
+    let window = web_sys::window()?;
+    let height = window.document()?.document_element()?.scroll_height() as f64;
+    let bottom = window.scroll_y().ok()? + window.inner_height().ok()?.as_f64()?;
+    if height <= 0.0 {
+        return None;
+    }
+    Some((bottom / height * 100.0).min(100.0) as u8)
+}
+
+struct Reading {
+    path: String,
+    /// the time it started in milliseconds
+    start: f64,
+    /// the deepest percent scrolled to
+    depth: Cell<u8>,
+    finished: Cell<bool>,
+}
+
+impl Reading {
+    fn scrolled(&self) {
+        if let Some(depth) = scroll_depth() {
+            self.depth.set(self.depth.get().max(depth));
+        }
+    }
+
+    /// emit the read once
+    fn finish(&self) {
+        if self.finished.replace(true) {
+            return;
+        }
+        let depth = self.depth.get();
+        emit(&Event::Read {
+            path: self.path.clone(),
+            seconds: ((js_sys::Date::now() - self.start) / 1000.0) as u64,
+            depth,
+            completed: depth >= COMPLETED_DEPTH.with(|e| e.get()),
+        });
+    }
+}
+
+/// the reading of a post from the time it is shown, the time on it
+/// and the depth scrolled to are emitted once it is dropped,
+/// eg: another page is routed to, or the page is closed
+pub struct ReadTracker {
+    reading: Rc<Reading>,
+    on_scroll: Closure<dyn FnMut()>,
+    on_hide: Closure<dyn FnMut()>,
+}
+
+impl ReadTracker {
+    /// start reading the post at the path of route
+    pub fn new(path: &str) -> Self {
+        let reading = Rc::new(Reading {
+            path: path.to_string(),
+            start: js_sys::Date::now(),
+            depth: Cell::new(0),
+            finished: Cell::new(false),
+        });
+        // the short posts are read through once shown
+        reading.scrolled();
+        let scrolled = reading.clone();
+        let on_scroll = Closure::wrap(Box::new(move || scrolled.scrolled()) as Box<dyn FnMut()>);
+        let hidden = reading.clone();
+        let on_hide = Closure::wrap(Box::new(move || hidden.finish()) as Box<dyn FnMut()>);
+        let tracker = Self {
+            reading,
+            on_scroll,
+            on_hide,
+        };
+        if let Some(window) = web_sys::window() {
+            for (name, callback) in tracker.listeners().iter() {
+                if let Err(e) = window.add_event_listener_with_callback(name, callback) {
+                    log::error!("{} of post not tracked: {:?}", name, e);
+                }
+            }
+        }
+        tracker
+    }
+
+    fn listeners(&self) -> [(&'static str, &js_sys::Function); 2] {
+        [
+            ("scroll", self.on_scroll.as_ref().unchecked_ref()),
+            ("pagehide", self.on_hide.as_ref().unchecked_ref()),
+        ]
+    }
+}
+
+impl Drop for ReadTracker {
+    fn drop(&mut self) {
+        if let Some(window) = web_sys::window() {
+            for (name, callback) in self.listeners().iter() {
+                let _ = window.remove_event_listener_with_callback(name, callback);
+            }
+        }
+        self.reading.finish();
+    }
+}
@@ -0,0 +1,169 @@
+//! Here provide the persistent cache of fetched files in `localStorage`
+//! each entry keeps the body with the validators (ETag, Last-Modified)
+//! of the response to revalidate it, and the parsed blog if it is a post.
+//! the entries least recently used are evicted once the cache exceeds
+//! `constant::CACHE_MAX_BYTES`
+use crate::constant;
+use crate::Blog;
+use serde::{Deserialize, Serialize};
+use web_sys::Storage;
+
+const PREFIX: &str = "noer-cache:";
+/// the key of index of entries, (url, size, last used time)
+const INDEX_KEY: &str = "noer-cache-index";
+
+/// a cached response of url
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub body: String,
+    #[serde(default)]
+    pub etag: String,
+    #[serde(default)]
+    pub last_modified: String,
+    /// the blog parsed from body
+    #[serde(default)]
+    pub blog: Option<Blog>,
+}
+
+impl Entry {
+    pub fn new(body: String, etag: String, last_modified: String) -> Self {
+        Self {
+            body,
+            etag,
+            last_modified,
+            blog: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct IndexItem {
+    url: String,
+    size: usize,
+    used: f64,
+}
+
+fn storage() -> Option<Storage> {
+    web_sys::window().and_then(|w| w.local_storage().ok().flatten())
+}
+
+fn read_index(storage: &Storage) -> Vec<IndexItem> {
+    storage
+        .get_item(INDEX_KEY)
+        .ok()
+        .flatten()
+        .and_then(|e| serde_json::from_str(&e).ok())
+        .unwrap_or_default()
+}
+
+fn write_index(storage: &Storage, index: &[IndexItem]) {
+    if let Ok(s) = serde_json::to_string(index) {
+        if let Err(e) = storage.set_item(INDEX_KEY, &s) {
+            log::error!("cache index not saved: {:?}", e);
+        }
+    }
+}
+
+/// the cached entry of url
+pub fn get(url: &str) -> Option<Entry> {
+    let storage = storage()?;
+    let entry = storage
+        .get_item(&format!("{}{}", PREFIX, url))
+        .ok()
+        .flatten()
+        .and_then(|e| serde_json::from_str::<Entry>(&e).ok())?;
+    touch(&storage, url);
+    Some(entry)
+}
+
+/// the blog parsed from the cached body of url
+pub fn get_blog(url: &str) -> Option<Blog> {
+    get(url).and_then(|e| e.blog)
+}
+
+/// cache the entry of url, the least recently used entries
+/// are evicted to make room for it
+pub fn put(url: &str, entry: &Entry) {
+    let storage = match storage() {
+        Some(storage) => storage,
+        None => return,
+    };
+    let value = match serde_json::to_string(entry) {
+        Ok(value) => value,
+        Err(e) => {
+            log::error!("cache entry of {} not serialized: {}", url, e);
+            return;
+        }
+    };
+    let size = value.len();
+    if size > constant::CACHE_MAX_BYTES {
+        log::debug!("{} is too large to cache", url);
+        return;
+    }
+    let mut index = read_index(&storage);
+    index.retain(|e| e.url != url);
+    evict(&storage, &mut index, constant::CACHE_MAX_BYTES - size);
+    // the quota of storage may be less than the limit
+    while storage
+        .set_item(&format!("{}{}", PREFIX, url), &value)
+        .is_err()
+    {
+        if index.is_empty() {
+            log::error!("{} not cached, the storage is full", url);
+            write_index(&storage, &index);
+            return;
+        }
+        evict(&storage, &mut index, 0);
+    }
+    index.push(IndexItem {
+        url: url.into(),
+        size,
+        used: js_sys::Date::now(),
+    });
+    write_index(&storage, &index);
+}
+
+/// cache the blog parsed from the cached body of url
+pub fn put_blog(url: &str, blog: &Blog) {
+    if let Some(mut entry) = get(url) {
+        if entry.blog.as_ref() != Some(blog) {
+            entry.blog = Some(blog.clone());
+            put(url, &entry);
+        }
+    }
+}
+
+/// drop the entry of url, eg: it is deleted on the server
+pub fn remove(url: &str) {
+    if let Some(storage) = storage() {
+        let _ = storage.remove_item(&format!("{}{}", PREFIX, url));
+        let mut index = read_index(&storage);
+        index.retain(|e| e.url != url);
+        write_index(&storage, &index);
+    }
+}
+
+/// mark the entry of url as used now
+fn touch(storage: &Storage, url: &str) {
+    let mut index = read_index(storage);
+    if let Some(item) = index.iter_mut().find(|e| e.url == url) {
+        item.used = js_sys::Date::now();
+        write_index(storage, &index);
+    }
+}
+
+/// evict the least recently used entries until the total size
+/// is not greater than `limit`, at least one is evicted if `limit` is 0
+fn evict(storage: &Storage, index: &mut Vec<IndexItem>, limit: usize) {
+    index.sort_by(|a, b| b.used.partial_cmp(&a.used).unwrap());
+    let mut total = index.iter().map(|e| e.size).sum::<usize>();
+    while !index.is_empty() && (total > limit || limit == 0) {
+        let item = index.pop().unwrap();
+        log::debug!("evict {} from cache", item.url);
+        let _ = storage.remove_item(&format!("{}{}", PREFIX, item.url));
+        total -= item.size;
+        if limit == 0 {
+            break;
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! `noer build`, build the site into the output directory
+//! the wasm is built by trunk, then the indexes, the feed and the pages
+//! for static hosts are generated into it, the source is left untouched
+use super::feed::{post_url, rss};
+use super::index::{load_blogs, markdown_index, redirects_index, redirects_to_lines, to_lines};
+use super::{load_config, Args, INDEX, REDIRECTS, SITE_CONFIG};
+use crate::common::content::Blog;
+use crate::config::SiteConfig;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// the posts with their front matter, for the tools
+const MANIFEST: &str = "posts/manifest.json";
+const FEED: &str = "feed.xml";
+/// the page static hosts serve for the missing paths,
+/// `__SUBPATH__` in it is filled with the sub-path
+const NOT_FOUND: &str = "404.html";
+const DEFAULT_NOT_FOUND: &str = include_str!("../../404.html");
+
+/// a post in manifest, the content is left out
+#[derive(Serialize)]
+struct ManifestItem<'a> {
+    path: String,
+    id: u64,
+    title: &'a str,
+    date: &'a str,
+    url: String,
+    tags: &'a [String],
+    authors: &'a [String],
+    lang: &'a str,
+    translation: &'a str,
+    series: &'a str,
+    aliases: &'a [String],
+    published: bool,
+}
+
+fn manifest(config: &SiteConfig, blogs: &[Blog]) -> Result<String, String> {
+    let items = blogs
+        .iter()
+        .map(|blog| ManifestItem {
+            path: blog.meta.path.to_string_lossy().to_string(),
+            id: blog.meta.id,
+            title: &blog.meta.title,
+            date: &blog.meta.date,
+            url: post_url(config, blog),
+            tags: &blog.tags,
+            authors: &blog.authors,
+            lang: &blog.lang,
+            translation: &blog.translation,
+            series: &blog.series,
+            aliases: &blog.aliases,
+            published: blog.published,
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_string_pretty(&items).map_err(|e| e.to_string())
+}
+
+/// the url of the root, eg: "/noer/"
+pub fn base(config: &SiteConfig) -> String {
+    match config.subpath.as_str() {
+        "/" => "/".into(),
+        subpath => format!("/{}", subpath),
+    }
+}
+
+fn write(out: &Path, path: &str, content: impl AsRef<[u8]>) -> Result<(), String> {
+    let file = out.join(path.trim_start_matches('/'));
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+    fs::write(&file, content).map_err(|e| format!("{}: {}", file.display(), e))
+}
+
+/// build the wasm and copy the files linked in `index.html` into `out`
+fn trunk(out: &Path, base: &str) -> Result<(), String> {
+    println!(
+        "trunk build --release --public-url {} --dist {}",
+        base,
+        out.display()
+    );
+    let status = Command::new("trunk")
+        .args(["build", "--release", "--public-url", base, "--dist"])
+        .arg(out)
+        .status()
+        .map_err(|e| format!("trunk not run: {}, install it by `cargo install trunk`", e))?;
+    if !status.success() {
+        return Err(format!("trunk build failed: {}", status));
+    }
+    Ok(())
+}
+
+/// the files generated from the posts and site config
+fn generate(config: &SiteConfig, out: &Path) -> Result<(), String> {
+    let posts = markdown_index();
+    let blogs = load_blogs(&posts);
+    generate_from(config, &posts, &blogs, out)
+}
+
+/// the files generated from the paths of posts in index and the blogs parsed
+fn generate_from(
+    config: &SiteConfig,
+    posts: &[String],
+    blogs: &[Blog],
+    out: &Path,
+) -> Result<(), String> {
+    write(out, INDEX, to_lines(posts))?;
+    let redirects = redirects_index(blogs);
+    write(out, REDIRECTS, redirects_to_lines(&redirects))?;
+    write(out, MANIFEST, manifest(config, blogs)?)?;
+    if config.url.is_empty() {
+        eprintln!(
+            "warning: `url` is not set in {}, {} is not generated",
+            SITE_CONFIG, FEED
+        );
+    } else {
+        write(out, FEED, rss(config, blogs))?;
+    }
+
+    // github pages serves the files starting with `_` without jekyll
+    write(out, ".nojekyll", "")?;
+    if Path::new("CNAME").is_file() {
+        let cname = fs::read("CNAME").map_err(|e| format!("CNAME: {}", e))?;
+        write(out, "CNAME", cname)?;
+    }
+    // static hosts serve it for the routes, it redirects to the site,
+    // the old paths of posts are given a copy of it to redirect
+    let not_found = fs::read_to_string(NOT_FOUND)
+        .unwrap_or_else(|_| DEFAULT_NOT_FOUND.into())
+        .replace("__SUBPATH__", &base(config));
+    write(out, NOT_FOUND, &not_found)?;
+    for (alias, _) in redirects.iter().filter(|(e, _)| !e.ends_with(".rmd")) {
+        let dir = alias.trim_matches('/');
+        if dir.is_empty() || dir.split('/').any(|e| e == "..") {
+            eprintln!("warning: alias {:?} ignored", alias);
+            continue;
+        }
+        write(out, &format!("{}/index.html", dir), &not_found)?;
+    }
+    Ok(())
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &["gitpage"])?;
+    args.check(&["out", "gitpage"])?;
+    let (config, errors) = load_config();
+    for e in errors {
+        eprintln!("warning: {}: {}", SITE_CONFIG, e);
+    }
+    let out = match (args.option("out"), args.flag("gitpage")) {
+        (Some(out), _) => out,
+        (None, true) => "docs",
+        (None, false) => "dist",
+    };
+    let out = Path::new(out);
+    trunk(out, &base(&config))?;
+    generate(&config, out)?;
+    println!("built into {}", out.display());
+    Ok(())
+}
+
+#[test]
+fn test_build() {
+    use crate::common::content::manifest_authors;
+    let out = super::test_dir("build");
+    let (config, _) = SiteConfig::from_toml("subpath = \"noer\"\nurl = \"https://a.io/noer\"");
+    let posts = super::fixture_posts();
+    let blogs = load_blogs(&posts);
+    generate_from(&config, &posts, &blogs, &out).unwrap();
+    let index = fs::read_to_string(out.join(INDEX)).unwrap();
+    assert_eq!(index, to_lines(&posts));
+    let not_found = fs::read_to_string(out.join(NOT_FOUND)).unwrap();
+    assert!(not_found.contains("\"/noer/\"") && !not_found.contains("__SUBPATH__"));
+    assert!(out.join("2021/10/10/first-post.html/index.html").is_file());
+    let feed = fs::read_to_string(out.join(FEED)).unwrap();
+    assert!(feed.contains("<link>https://a.io/noer/posts/"));
+    let manifest = fs::read_to_string(out.join(MANIFEST)).unwrap();
+    let authors = manifest_authors(&manifest).unwrap();
+    assert_eq!(authors[&blogs[1].meta.id], vec!["Alice"]);
+    assert!(out.join(".nojekyll").is_file());
+    fs::remove_dir_all(&out).unwrap();
+}
@@ -0,0 +1,395 @@
+//! `noer check`, validate the posts before they are published
+//! the problems are printed as text, or as json with `--json`
+use super::{list_posts, load_config, now, read_index, Args, INDEX, POSTS_DIR, SITE_CONFIG};
+use crate::common::content::{slugify, Blog, BlogMeta};
+use crate::common::date;
+use crate::common::front_matter::{keys, parse_blog, KEYS};
+use crate::common::markdown;
+use crate::common::sanitize::{sanitize_report, SanitizeConfig};
+use pulldown_cmark::{Event, Tag};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Error,
+    Warning,
+}
+
+/// a problem found in the file
+#[derive(Debug, Serialize)]
+pub struct Issue {
+    pub path: String,
+    pub level: Level,
+    /// the kind of problem, eg: "broken-link"
+    pub code: &'static str,
+    pub message: String,
+}
+
+#[derive(Default)]
+struct Checker {
+    issues: Vec<Issue>,
+}
+
+impl Checker {
+    fn push(&mut self, path: &str, level: Level, code: &'static str, message: String) {
+        self.issues.push(Issue {
+            path: path.to_string(),
+            level,
+            code,
+            message,
+        });
+    }
+
+    fn error(&mut self, path: &str, code: &'static str, message: String) {
+        self.push(path, Level::Error, code, message)
+    }
+
+    fn warn(&mut self, path: &str, code: &'static str, message: String) {
+        self.push(path, Level::Warning, code, message)
+    }
+
+    fn count(&self, level: Level) -> usize {
+        self.issues.iter().filter(|e| e.level == level).count()
+    }
+}
+
+fn is_leap(year: u64) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+fn days_in_month(year: u64, month: u64) -> u64 {
+    match month {
+        2 if is_leap(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// the date in file name is a real one and not in the future
+fn check_date(checker: &mut Checker, meta: &BlogMeta, now: [u64; 6]) {
+    let path = meta.path.to_string_lossy();
+    let date = date::parse_date(&meta.date);
+    let [year, month, day, hour, minute, second] = date;
+    let valid = (1..=12).contains(&month)
+        && (1..=days_in_month(year, month)).contains(&day)
+        && hour < 24
+        && minute < 60
+        && second < 60;
+    if !valid {
+        checker.error(
+            &path,
+            "invalid-date",
+            format!("{:?} is not a valid date", meta.date),
+        );
+    } else if date > now {
+        checker.warn(
+            &path,
+            "future-date",
+            format!("the date {:?} is in the future", meta.date),
+        );
+    }
+}
+
+/// the front matter has the required keys and no unknown ones
+fn check_front_matter(checker: &mut Checker, text: &str, meta: &BlogMeta) -> Option<Blog> {
+    let path = meta.path.to_string_lossy();
+    for key in keys(text) {
+        if key == "trusted" {
+            checker.warn(
+                &path,
+                "trusted-key",
+                "`trusted` in front matter is ignored, the trusted posts are listed in [sanitize] of site.toml".into(),
+            );
+        } else if !KEYS.contains(&key.as_str()) {
+            checker.warn(
+                &path,
+                "unknown-key",
+                format!("`{}` in front matter is unknown", key),
+            );
+        }
+    }
+    match parse_blog(text, meta) {
+        Ok(blog) if blog.ignored => {
+            checker.error(&path, "no-date", "the date of post is unknown".into());
+            None
+        }
+        Ok(blog) => Some(blog),
+        Err(e) => {
+            checker.error(&path, "front-matter", e);
+            None
+        }
+    }
+}
+
+/// the path in the site that the url refers to,
+/// None if it is external or only a fragment
+fn local_path(url: &str, subpath: &str) -> Option<String> {
+    let scheme = regex::Regex::new(r"^[a-zA-Z][a-zA-Z0-9+.-]*:").unwrap();
+    if url.is_empty() || url.starts_with('#') || url.starts_with("//") || scheme.is_match(url) {
+        return None;
+    }
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+    let path = url.trim_start_matches('/');
+    let path = match subpath {
+        "/" => path,
+        subpath => path.strip_prefix(subpath).unwrap_or(path),
+    };
+    Some(path.trim_end_matches('/').to_string())
+}
+
+/// the internal links refer to existing posts or files,
+/// the local images exist and have alt text
+fn check_links(checker: &mut Checker, blog: &Blog, ids: &HashMap<u64, String>, subpath: &str) {
+    let path = blog.meta.path.to_string_lossy();
+    let post_route = regex::Regex::new(r"^posts/(?P<id>\d+)(/|$)").unwrap();
+    let content = blog.content.join("\n\n");
+    // the image whose alt text is being read
+    let mut image: Option<(String, String)> = None;
+    for event in pulldown_cmark::Parser::new_ext(&content, markdown::options()) {
+        match event {
+            Event::Start(Tag::Link(_, url, _)) => {
+                let target = match local_path(&url, subpath) {
+                    Some(target) => target,
+                    None => continue,
+                };
+                if let Some(caps) = post_route.captures(&target) {
+                    let id = caps["id"].parse::<u64>();
+                    if !matches!(id, Ok(id) if ids.contains_key(&id)) {
+                        checker.error(&path, "broken-link", format!("{} refers to no post", url));
+                    }
+                    continue;
+                }
+                let route = target.is_empty()
+                    || ["authors/", "lang/"].iter().any(|e| target.starts_with(e));
+                if !route && !Path::new(&target).exists() {
+                    checker.error(&path, "broken-link", format!("{} does not exist", url));
+                }
+            }
+            Event::Start(Tag::Image(_, url, _)) => {
+                if let Some(target) = local_path(&url, subpath) {
+                    if !Path::new(&target).is_file() {
+                        checker.error(&path, "missing-image", format!("{} does not exist", url));
+                    }
+                }
+                image = Some((url.to_string(), String::new()));
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, alt)) = image.as_mut() {
+                    alt.push_str(&text);
+                }
+            }
+            Event::End(Tag::Image(..)) => {
+                if let Some((url, alt)) = image.take() {
+                    if alt.trim().is_empty() {
+                        checker.warn(&path, "missing-alt", format!("{} has no alt text", url));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// warn of the HTML removed by sanitizing, or kept as the post is trusted
+fn check_html(checker: &mut Checker, blog: &Blog, config: &SanitizeConfig) {
+    let path = blog.meta.path.to_string_lossy();
+    let html = markdown::to_html(&blog.content.join("\n\n"));
+    let (_, removed) = sanitize_report(&html, config);
+    if removed.is_empty() {
+        return;
+    }
+    if config.trusts(&blog.meta.path) {
+        checker.warn(
+            &path,
+            "trusted",
+            format!(
+                "{} kept as the post is trusted in [sanitize] of site.toml",
+                removed.join(", ")
+            ),
+        );
+    } else {
+        checker.warn(
+            &path,
+            "sanitized",
+            format!(
+                "{} removed when rendered, allow them in [sanitize] or list the post in its `trusted`",
+                removed.join(", ")
+            ),
+        );
+    }
+}
+
+/// check the site config, the index and every post
+fn check() -> Checker {
+    let mut checker = Checker::default();
+    let (config, errors) = load_config();
+    for e in errors {
+        checker.error(SITE_CONFIG, "site-config", e);
+    }
+
+    let index = read_index();
+    let files = list_posts();
+    let mut seen = BTreeSet::new();
+    for path in &index {
+        if !seen.insert(path.as_str()) {
+            checker.warn(
+                INDEX,
+                "duplicate-entry",
+                format!("{} is listed twice", path),
+            );
+        }
+        if !Path::new(path).is_file() {
+            checker.error(INDEX, "missing-file", format!("{} does not exist", path));
+        }
+    }
+    for path in &files {
+        if !seen.contains(path.as_str()) {
+            checker.warn(path, "not-indexed", format!("it is not in {}", INDEX));
+        }
+    }
+
+    let now = now();
+    let mut blogs = Vec::new();
+    let paths = index
+        .iter()
+        .chain(files.iter())
+        .filter(|e| Path::new(e).is_file())
+        .collect::<BTreeSet<_>>();
+    for path in paths {
+        let meta = match BlogMeta::from_path(path) {
+            Some(meta) => meta,
+            None => {
+                checker.error(
+                    path,
+                    "file-name",
+                    "the file name shall be like yyyy-mm-dd-title.rmd".into(),
+                );
+                continue;
+            }
+        };
+        check_date(&mut checker, &meta, now);
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => {
+                checker.error(path, "unreadable", e.to_string());
+                continue;
+            }
+        };
+        if let Some(blog) = check_front_matter(&mut checker, &text, &meta) {
+            blogs.push(blog);
+        }
+    }
+
+    let mut ids = HashMap::new();
+    let mut slugs = HashMap::new();
+    for blog in &blogs {
+        let path = blog.meta.path.to_string_lossy().to_string();
+        if let Some(other) = ids.insert(blog.meta.id, path.clone()) {
+            checker.error(
+                &path,
+                "duplicate-id",
+                format!("the id {} is the same as {}", blog.meta.id, other),
+            );
+        }
+        if let Some(other) = slugs.insert(slugify(&blog.meta.title), path.clone()) {
+            checker.warn(
+                &path,
+                "duplicate-slug",
+                format!("the title {:?} is the same as {}", blog.meta.title, other),
+            );
+        }
+    }
+    for blog in &blogs {
+        check_links(&mut checker, blog, &ids, &config.subpath);
+        check_html(&mut checker, blog, &config.sanitize);
+    }
+    checker
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &["json"])?;
+    args.check(&["json"])?;
+    if !Path::new(POSTS_DIR).is_dir() {
+        return Err(format!(
+            "{} not found, run it in the root of site",
+            POSTS_DIR
+        ));
+    }
+    let checker = check();
+    let errors = checker.count(Level::Error);
+    let warnings = checker.count(Level::Warning);
+    if args.flag("json") {
+        let json = serde_json::json!({
+            "errors": errors,
+            "warnings": warnings,
+            "issues": checker.issues,
+        });
+        println!("{}", json);
+    } else {
+        for issue in &checker.issues {
+            let level = match issue.level {
+                Level::Error => "error",
+                Level::Warning => "warning",
+            };
+            println!(
+                "{}: {}[{}]: {}",
+                issue.path, level, issue.code, issue.message
+            );
+        }
+        println!("{} errors, {} warnings", errors, warnings);
+    }
+    match errors {
+        0 => Ok(()),
+        n => Err(format!("{} errors found", n)),
+    }
+}
+
+#[test]
+fn test_check() {
+    assert_eq!(local_path("https://a.com/b.png", "noer/"), None);
+    assert_eq!(local_path("mailto:a@b.c", "noer/"), None);
+    assert_eq!(local_path("#intro", "noer/"), None);
+    assert_eq!(
+        local_path("/noer/assets/a.png?v=1", "noer/"),
+        Some("assets/a.png".into())
+    );
+    assert_eq!(
+        local_path("/posts/1/title/", "/"),
+        Some("posts/1/title".into())
+    );
+
+    let mut checker = Checker::default();
+    let mut meta = BlogMeta::from_path("posts/2021-02-30-demo.rmd").unwrap();
+    check_date(&mut checker, &meta, [2022, 1, 1, 0, 0, 0]);
+    meta.date = "2020-02-29".into();
+    check_date(&mut checker, &meta, [2022, 1, 1, 0, 0, 0]);
+    meta.date = "2023-01-01".into();
+    check_date(&mut checker, &meta, [2022, 1, 1, 0, 0, 0]);
+    let codes = checker.issues.iter().map(|e| e.code).collect::<Vec<_>>();
+    assert_eq!(codes, vec!["invalid-date", "future-date"]);
+
+    let text = "---\ntitle: demo\npublished: true\ncolour: red\n---\n![](/a.png) [x](/posts/1/x)";
+    let blog = check_front_matter(&mut checker, text, &meta).unwrap();
+    check_links(&mut checker, &blog, &HashMap::new(), "/");
+    let codes = checker.issues.iter().map(|e| e.code).collect::<Vec<_>>();
+    assert_eq!(
+        codes[2..],
+        ["unknown-key", "missing-image", "missing-alt", "broken-link"]
+    );
+
+    let text = "---\ntitle: demo\npublished: true\ntrusted: true\n---\n<script>x()</script>";
+    let blog = check_front_matter(&mut checker, text, &meta).unwrap();
+    check_html(&mut checker, &blog, &SanitizeConfig::default());
+    let config = SanitizeConfig {
+        trusted: vec!["2021-02-30-demo".into()],
+        ..Default::default()
+    };
+    check_html(&mut checker, &blog, &config);
+    let codes = checker.issues.iter().map(|e| e.code).collect::<Vec<_>>();
+    assert_eq!(codes[6..], ["trusted-key", "sanitized", "trusted"]);
+}
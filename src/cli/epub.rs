@@ -0,0 +1,466 @@
+//! `noer export`, the posts selected into an EPUB 3 book, a chapter per post
+//! the posts are rendered as the site renders them, with the local images
+//! embedded and the links among them made into the links among chapters
+use super::build::base;
+use super::feed::post_url;
+use super::index::{load_blogs, markdown_index};
+use super::zip::Zip;
+use super::{civil, load_config, Args, SITE_CONFIG};
+use crate::common::content::{slugify, Blog};
+use crate::common::markdown::to_html;
+use crate::common::markup::{escape, parse_html, Element, Node};
+use crate::common::sanitize::sanitize_report;
+use crate::config::SiteConfig;
+use crate::i18n;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const CONTAINER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+const STYLE: &str = "body { font-family: serif; line-height: 1.5; }
+h1 { margin-bottom: 0.2em; }
+.byline { color: #666; font-size: 0.9em; }
+pre { white-space: pre-wrap; font-size: 0.85em; background: #f5f5f5; padding: 0.5em; }
+code { font-family: monospace; }
+img { max-width: 100%; }
+blockquote { margin-left: 1em; padding-left: 1em; border-left: 3px solid #ccc; }
+table { border-collapse: collapse; }
+td, th { border: 1px solid #ccc; padding: 0.2em 0.5em; }
+";
+
+/// the media type of image by its extension, None if it is not one
+fn media_type(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    match ext.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "svg" => Some("image/svg+xml"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+fn chapter_file(index: usize) -> String {
+    format!("chapter-{:03}.xhtml", index + 1)
+}
+
+/// the XHTML document of body
+fn xhtml(lang: &str, title: &str, body: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\n<html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" lang=\"{}\" xml:lang=\"{}\">\n<head>\n  <meta charset=\"UTF-8\"/>\n  <title>{}</title>\n  <link rel=\"stylesheet\" type=\"text/css\" href=\"style.css\"/>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(lang),
+        escape(lang),
+        escape(title),
+        body
+    )
+}
+
+struct Book<'a> {
+    config: &'a SiteConfig,
+    /// the url of the root, eg: "/noer/"
+    base: String,
+    /// the files of chapters by the ids of posts
+    chapters: HashMap<u64, String>,
+    /// the local images, the file and its path in book
+    images: Vec<(PathBuf, String)>,
+    warnings: Vec<String>,
+}
+
+impl Book<'_> {
+    /// the path of local image in book, it is added if it is not yet,
+    /// None if it is not found
+    fn image(&mut self, src: &str) -> Option<String> {
+        let path = src.split(['?', '#']).next().unwrap_or_default();
+        let path = path
+            .strip_prefix(self.base.as_str())
+            .or_else(|| path.strip_prefix('/'))
+            .unwrap_or(path);
+        let file = PathBuf::from(path);
+        media_type(&file)?;
+        if !file.is_file() {
+            return None;
+        }
+        if let Some((_, name)) = self.images.iter().find(|(e, _)| *e == file) {
+            return Some(name.clone());
+        }
+        let name = file
+            .file_name()
+            .map(|e| e.to_string_lossy())
+            .unwrap_or_default()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect::<String>();
+        let name = format!("images/{}-{}", self.images.len() + 1, name);
+        self.images.push((file, name.clone()));
+        Some(name)
+    }
+
+    /// the link in book, to the chapter if it is a post in book,
+    /// or to the site if its url is known
+    fn link(&self, href: &str) -> Option<String> {
+        let route = regex::Regex::new(r"^(?:#/)?posts/(?P<id>\d+)(?:/|$)").unwrap();
+        let path = href
+            .strip_prefix(self.base.as_str())
+            .or_else(|| href.strip_prefix('/'))
+            .or_else(|| Some(href).filter(|e| e.starts_with("#/")))?;
+        let chapter = route
+            .captures(path)
+            .and_then(|e| e["id"].parse().ok())
+            .and_then(|id| self.chapters.get(&id));
+        match chapter {
+            Some(chapter) => Some(chapter.clone()),
+            None if !self.config.url.is_empty() => Some(format!("{}{}", self.config.url, path)),
+            None => None,
+        }
+    }
+
+    /// the images and links of posts made to work in book,
+    /// the images not embedded are replaced by their alternative text
+    fn rewrite(&mut self, element: &mut Element) {
+        let scheme = regex::Regex::new(r"^([a-zA-Z][a-zA-Z0-9+.-]*:|//)").unwrap();
+        for child in element.children.iter_mut() {
+            let e = match child {
+                Node::Element(e) => e,
+                _ => continue,
+            };
+            match e.name.as_str() {
+                "img" => {
+                    let src = e.attr("src").unwrap_or_default().to_string();
+                    let local = match scheme.is_match(&src) {
+                        true => None,
+                        false => self.image(&src),
+                    };
+                    match local {
+                        Some(local) => {
+                            e.attrs.retain(|(k, _)| k != "src");
+                            e.attrs.push(("src".into(), local));
+                            if e.attr("alt").is_none() {
+                                e.attrs.push(("alt".into(), String::new()));
+                            }
+                        }
+                        None => {
+                            self.warnings.push(format!("image not embedded: {}", src));
+                            *child = Node::Text(e.attr("alt").unwrap_or_default().to_string());
+                        }
+                    }
+                }
+                "a" => {
+                    let href = e.attr("href").unwrap_or_default();
+                    if (!scheme.is_match(href) && !href.starts_with('#')) || href.starts_with("#/")
+                    {
+                        if let Some(link) = self.link(href) {
+                            e.attrs.retain(|(k, _)| k != "href");
+                            e.attrs.push(("href".into(), link));
+                        }
+                    }
+                    self.rewrite(e);
+                }
+                _ => self.rewrite(e),
+            }
+        }
+    }
+
+    /// the chapter of post
+    fn chapter(&mut self, blog: &Blog) -> String {
+        let title = blog.meta.title.replace('-', " ");
+        let lang = blog.lang_or(&book_lang(self.config));
+        let authors = blog
+            .authors_or(&self.config.admin)
+            .join(self.config.t("and"));
+        let mut body = format!(
+            "<section epub:type=\"chapter\">\n<h1>{}</h1>\n<p class=\"byline\">{} · {}{}</p>\n",
+            escape(&title),
+            escape(&blog.meta.date),
+            escape(self.config.t("by")),
+            escape(&authors)
+        );
+        for part in &blog.content {
+            // sanitized as the site renders it
+            let html = match self.config.sanitize.trusts(&blog.meta.path) {
+                true => to_html(part),
+                false => {
+                    let (html, removed) = sanitize_report(&to_html(part), &self.config.sanitize);
+                    if !removed.is_empty() {
+                        self.warnings.push(format!(
+                            "removed from {:?}: {}",
+                            blog.meta.path,
+                            removed.join(", ")
+                        ));
+                    }
+                    html
+                }
+            };
+            let mut root = parse_html(&html);
+            self.rewrite(&mut root);
+            body.push_str("<div class=\"markdown-body\">\n");
+            root.write_children(&mut body, true);
+            body.push_str("</div>\n");
+        }
+        if !self.config.url.is_empty() {
+            let url = escape(&post_url(self.config, blog));
+            body.push_str(&format!(
+                "<p class=\"source\"><a href=\"{}\">{}</a></p>\n",
+                url, url
+            ));
+        }
+        body.push_str("</section>\n");
+        xhtml(&lang, &title, &body)
+    }
+}
+
+/// the language of book, the site language
+fn book_lang(config: &SiteConfig) -> String {
+    match config.language.as_str() {
+        "auto" | "" => i18n::DEFAULT_LANG.into(),
+        lang => lang.into(),
+    }
+}
+
+/// the date and time in the format of `dcterms:modified`
+fn modified(secs: u64) -> String {
+    let [year, month, day, hour, minute, second] = civil(secs);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, hour, minute, second
+    )
+}
+
+/// the book of posts in the order of chapters, the warnings are returned with it
+pub fn epub(
+    config: &SiteConfig,
+    title: &str,
+    blogs: &[&Blog],
+    secs: u64,
+) -> Result<(Vec<u8>, Vec<String>), String> {
+    let mut book = Book {
+        config,
+        base: base(config),
+        chapters: blogs
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.meta.id, chapter_file(i)))
+            .collect(),
+        images: Vec::new(),
+        warnings: Vec::new(),
+    };
+    let lang = book_lang(config);
+    let chapters = blogs.iter().map(|e| book.chapter(e)).collect::<Vec<_>>();
+    let titles = blogs
+        .iter()
+        .map(|e| escape(&e.meta.title.replace('-', " ")))
+        .collect::<Vec<_>>();
+
+    let mut zip = Zip::new(secs);
+    // it is the first and stored, so readers know the type by its offset
+    zip.add("mimetype", b"application/epub+zip");
+    zip.add("META-INF/container.xml", CONTAINER.as_bytes());
+    zip.add("OEBPS/style.css", STYLE.as_bytes());
+
+    let contents = i18n::t(&lang, "contents");
+    let nav = titles
+        .iter()
+        .enumerate()
+        .map(|(i, e)| format!("    <li><a href=\"{}\">{}</a></li>\n", chapter_file(i), e))
+        .collect::<String>();
+    let nav = format!(
+        "<nav epub:type=\"toc\" id=\"toc\">\n  <h1>{}</h1>\n  <ol>\n{}  </ol>\n</nav>\n",
+        escape(contents),
+        nav
+    );
+    zip.add("OEBPS/nav.xhtml", xhtml(&lang, contents, &nav).as_bytes());
+
+    let id = match config.url.as_str() {
+        "" => format!("urn:noer:{}", slugify(title)),
+        url => format!("{}#{}", url, slugify(title)),
+    };
+    // the table of contents of EPUB 2, for the older readers
+    let points = titles
+        .iter()
+        .enumerate()
+        .map(|(i, e)| {
+            format!(
+                "    <navPoint id=\"p{}\" playOrder=\"{}\"><navLabel><text>{}</text></navLabel><content src=\"{}\"/></navPoint>\n",
+                i + 1,
+                i + 1,
+                e,
+                chapter_file(i)
+            )
+        })
+        .collect::<String>();
+    let ncx = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<ncx xmlns=\"http://www.daisy.org/z3986/2005/ncx/\" version=\"2005-1\">\n  <head><meta name=\"dtb:uid\" content=\"{}\"/></head>\n  <docTitle><text>{}</text></docTitle>\n  <navMap>\n{}  </navMap>\n</ncx>\n",
+        escape(&id),
+        escape(title),
+        points
+    );
+    zip.add("OEBPS/toc.ncx", ncx.as_bytes());
+
+    let mut manifest = String::from(
+        "    <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n    <item id=\"ncx\" href=\"toc.ncx\" media-type=\"application/x-dtbncx+xml\"/>\n    <item id=\"style\" href=\"style.css\" media-type=\"text/css\"/>\n",
+    );
+    let mut spine = String::from("    <itemref idref=\"nav\"/>\n");
+    for (i, chapter) in chapters.iter().enumerate() {
+        zip.add(&format!("OEBPS/{}", chapter_file(i)), chapter.as_bytes());
+        manifest.push_str(&format!(
+            "    <item id=\"chapter-{}\" href=\"{}\" media-type=\"application/xhtml+xml\"/>\n",
+            i + 1,
+            chapter_file(i)
+        ));
+        spine.push_str(&format!("    <itemref idref=\"chapter-{}\"/>\n", i + 1));
+    }
+    for (i, (file, name)) in book.images.iter().enumerate() {
+        let bytes = fs::read(file).map_err(|e| format!("{}: {}", file.display(), e))?;
+        zip.add(&format!("OEBPS/{}", name), &bytes);
+        manifest.push_str(&format!(
+            "    <item id=\"image-{}\" href=\"{}\" media-type=\"{}\"/>\n",
+            i + 1,
+            name,
+            media_type(file).unwrap_or_default()
+        ));
+    }
+
+    let mut creators = Vec::new();
+    for author in blogs.iter().flat_map(|e| e.authors_or(&config.admin)) {
+        if !creators.contains(&author) {
+            creators.push(author);
+        }
+    }
+    let creators = creators
+        .iter()
+        .map(|e| format!("    <dc:creator>{}</dc:creator>\n", escape(e)))
+        .collect::<String>();
+    let source = match config.url.as_str() {
+        "" => String::new(),
+        url => format!("    <dc:source>{}</dc:source>\n", escape(url)),
+    };
+    let opf = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"book-id\" xml:lang=\"{}\">\n  <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n    <dc:identifier id=\"book-id\">{}</dc:identifier>\n    <dc:title>{}</dc:title>\n    <dc:language>{}</dc:language>\n{}    <dc:description>{}</dc:description>\n    <dc:publisher>{}</dc:publisher>\n{}    <meta property=\"dcterms:modified\">{}</meta>\n  </metadata>\n  <manifest>\n{}  </manifest>\n  <spine toc=\"ncx\">\n{}  </spine>\n</package>\n",
+        escape(&lang),
+        escape(&id),
+        escape(title),
+        escape(&lang),
+        creators,
+        escape(parse_html(&config.site_description).text().trim()),
+        escape(&config.site_name),
+        source,
+        modified(secs),
+        manifest,
+        spine
+    );
+    zip.add("OEBPS/content.opf", opf.as_bytes());
+    Ok((zip.finish(), book.warnings))
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &[])?;
+    args.check(&["tag", "series", "title", "out"])?;
+    if !args.values.is_empty() {
+        return Err(
+            "usage: noer export [--tag <tag> | --series <name>] [--title <title>] [--out <file>]"
+                .into(),
+        );
+    }
+    let (config, errors) = load_config();
+    for e in errors {
+        eprintln!("warning: {}: {}", SITE_CONFIG, e);
+    }
+    let blogs = load_blogs(&markdown_index());
+    let mut selected = blogs
+        .iter()
+        .filter(|e| e.published && !e.ignored)
+        .filter(|e| match args.option("tag") {
+            Some(tag) => e.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)),
+            None => true,
+        })
+        .filter(|e| match args.option("series") {
+            Some(series) => e.series == series,
+            None => true,
+        })
+        .collect::<Vec<_>>();
+    if selected.is_empty() {
+        return Err("no published posts selected".into());
+    }
+    // the chapters are in the order they are written
+    selected.sort_by_key(|e| e.meta.timestamp);
+
+    let title = match (
+        args.option("title"),
+        args.option("series"),
+        args.option("tag"),
+    ) {
+        (Some(title), _, _) => title.to_string(),
+        (None, Some(series), _) => series.to_string(),
+        (None, None, Some(tag)) => format!("{}: {}", config.site_name, tag),
+        (None, None, None) => config.site_name.clone(),
+    };
+    let out = match args.option("out") {
+        Some(out) => out.to_string(),
+        None => format!("{}.epub", slugify(&title)),
+    };
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|e| e.as_secs())
+        .unwrap_or(0);
+    let (data, warnings) = epub(&config, &title, &selected, secs)?;
+    for warning in warnings {
+        eprintln!("warning: {}", warning);
+    }
+    fs::write(&out, data).map_err(|e| format!("{}: {}", out, e))?;
+    println!("{} posts exported into {}", selected.len(), out);
+    Ok(())
+}
+
+#[test]
+fn test_epub() {
+    let (config, _) = SiteConfig::from_toml("subpath = \"noer\"\nurl = \"https://a.io/noer\"");
+    let blogs = load_blogs(&super::fixture_posts());
+    let mut selected = blogs.iter().collect::<Vec<_>>();
+    selected.sort_by_key(|e| e.meta.timestamp);
+    let (data, _) = epub(&config, "Tips", &selected, 0).unwrap();
+    assert_eq!(&data[30..38], b"mimetype");
+    let text = String::from_utf8_lossy(&data);
+    assert!(text.contains("OEBPS/chapter-002.xhtml") && !text.contains("chapter-003"));
+    assert!(text.contains("<dc:title>Tips</dc:title>"));
+
+    let mut book = Book {
+        config: &config,
+        base: base(&config),
+        chapters: vec![(7, chapter_file(0))].into_iter().collect(),
+        images: Vec::new(),
+        warnings: Vec::new(),
+    };
+    let mut root = parse_html(
+        "<p><a href=\"/noer/posts/7/a\">a</a> <a href=\"/noer/posts/8/b\">b</a> <img src=\"https://x.io/a.png\" alt=\"remote\"></p>",
+    );
+    book.rewrite(&mut root);
+    let mut s = String::new();
+    root.write_children(&mut s, true);
+    assert_eq!(
+        s,
+        "<p><a href=\"chapter-001.xhtml\">a</a> <a href=\"https://a.io/noer/posts/8/b\">b</a> remote</p>"
+    );
+    assert_eq!(book.warnings.len(), 1);
+
+    let mut blog = blogs[0].clone();
+    blog.content = vec!["hi <script>alert(1)</script>".into()];
+    assert!(!book.chapter(&blog).contains("<script"));
+    assert_eq!(book.warnings.len(), 2);
+    let mut config = config.clone();
+    config.sanitize.trusted = vec!["2022-01-20-second-post".into()];
+    book.config = &config;
+    assert!(book.chapter(&blog).contains("<script"));
+}
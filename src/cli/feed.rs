@@ -0,0 +1,87 @@
+//! Here provide the rss feed of the published posts
+use crate::common::content::Blog;
+use crate::common::date::parse_date;
+use crate::common::markdown::to_html;
+use crate::common::markup::escape;
+use crate::common::url::post_route;
+use crate::config::SiteConfig;
+
+/// number of the latest posts in feed
+const FEED_ITEMS: usize = 20;
+const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// the absolute url of post, as the app routes it
+pub fn post_url(config: &SiteConfig, blog: &Blog) -> String {
+    config.routing().url(&config.url, &post_route(&blog.meta))
+}
+
+/// the days since 1970-01-01
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// the date of post in rfc822, eg: "Sun, 10 Oct 2021 00:00:00 +0000"
+fn rfc822(date: &str) -> String {
+    let [year, month, day, hour, minute, second] = parse_date(date);
+    let month = month.clamp(1, 12);
+    let weekday = days_from_civil(year as i64, month as i64, day as i64).rem_euclid(7) as usize;
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} +0000",
+        WEEKDAYS[weekday],
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        hour,
+        minute,
+        second
+    )
+}
+
+/// the feed of the latest published posts, `blogs` are the latest first
+pub fn rss(config: &SiteConfig, blogs: &[Blog]) -> String {
+    let items = blogs
+        .iter()
+        .filter(|e| e.published && !e.ignored)
+        .take(FEED_ITEMS)
+        .map(|blog| {
+            let url = escape(&post_url(config, blog));
+            let summary = blog.content.first().map(|e| to_html(e)).unwrap_or_default();
+            let categories = blog
+                .tags
+                .iter()
+                .map(|e| format!("\n      <category>{}</category>", escape(e)))
+                .collect::<String>();
+            format!(
+                "    <item>\n      <title>{}</title>\n      <link>{}</link>\n      <guid>{}</guid>\n      <pubDate>{}</pubDate>{}\n      <description>{}</description>\n    </item>\n",
+                escape(&blog.meta.title.replace("-", " ")),
+                url,
+                url,
+                rfc822(&blog.meta.date),
+                categories,
+                escape(&summary)
+            )
+        })
+        .collect::<String>();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rss version=\"2.0\">\n  <channel>\n    <title>{}</title>\n    <link>{}</link>\n    <description>{}</description>\n{}  </channel>\n</rss>\n",
+        escape(&config.site_name),
+        escape(&config.url),
+        escape(config.site_description.trim()),
+        items
+    )
+}
+
+#[test]
+fn test_feed() {
+    assert_eq!(rfc822("2021-10-10"), "Sun, 10 Oct 2021 00:00:00 +0000");
+    assert_eq!(rfc822("22-03-11-08-05"), "Fri, 11 Mar 2022 08:05:00 +0000");
+}
@@ -0,0 +1,193 @@
+//! Here provide the reading of front matter of other blogs,
+//! YAML between `---` (the common subset of it) or TOML between `+++`
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Text(String),
+    List(Vec<String>),
+}
+
+#[derive(Debug, Default)]
+pub struct Fields(pub BTreeMap<String, Value>);
+
+impl Fields {
+    pub fn text(&self, key: &str) -> Option<&str> {
+        match self.0.get(key) {
+            Some(Value::Text(text)) if !text.is_empty() => Some(text),
+            _ => None,
+        }
+    }
+
+    /// the items of key, a text is separated by comma,
+    /// or by space if there is no comma, eg: `tags: rust wasm`
+    pub fn list(&self, key: &str) -> Vec<String> {
+        match self.0.get(key) {
+            Some(Value::List(items)) => items.clone(),
+            Some(Value::Text(text)) if text.contains(',') => {
+                text.split(',').map(|e| e.trim().to_string()).collect()
+            }
+            Some(Value::Text(text)) => text.split_whitespace().map(String::from).collect(),
+            None => vec![],
+        }
+    }
+
+    /// the items of key, a text is separated by comma only,
+    /// eg: `author: John Doe`
+    pub fn items(&self, key: &str) -> Vec<String> {
+        match self.0.get(key) {
+            Some(Value::List(items)) => items.clone(),
+            Some(Value::Text(text)) => text
+                .split(',')
+                .map(|e| e.trim().to_string())
+                .filter(|e| !e.is_empty())
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    pub fn flag(&self, key: &str) -> Option<bool> {
+        self.text(key).and_then(|e| e.parse().ok())
+    }
+}
+
+fn unquote(s: &str) -> String {
+    let s = s.trim();
+    let quoted = s.len() >= 2
+        && ((s.starts_with('"') && s.ends_with('"')) || (s.starts_with('\'') && s.ends_with('\'')));
+    if quoted {
+        s[1..s.len() - 1].replace("\\\"", "\"").replace("''", "'")
+    } else {
+        s.to_string()
+    }
+}
+
+/// the inline list, eg: `[a, "b c"]`
+fn inline_list(s: &str) -> Vec<String> {
+    s.trim_matches(|c| c == '[' || c == ']')
+        .split(',')
+        .map(unquote)
+        .filter(|e| !e.is_empty())
+        .collect()
+}
+
+/// the keys at the top of YAML, the nested maps are not read
+pub fn parse_yaml(s: &str) -> Fields {
+    let pat = regex::Regex::new(r"^(?P<key>[\w-]+)\s*:\s*(?P<value>.*)$").unwrap();
+    let mut fields = BTreeMap::new();
+    let mut key = String::new();
+    // the text block of key, eg: `description: >`
+    let mut block = false;
+    for line in s.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') || trimmed.is_empty() {
+            continue;
+        }
+        let indented = line.starts_with(' ') || line.starts_with('\t');
+        if !indented {
+            if let Some(caps) = pat.captures(line) {
+                key = caps["key"].to_string();
+                let value = caps["value"].trim();
+                block = value.starts_with('>') || value.starts_with('|');
+                let value = if value.is_empty() {
+                    Value::List(vec![])
+                } else if block {
+                    Value::Text(String::new())
+                } else if value.starts_with('[') {
+                    Value::List(inline_list(value))
+                } else {
+                    Value::Text(unquote(value))
+                };
+                fields.insert(key.clone(), value);
+                continue;
+            }
+        }
+        match (fields.get_mut(&key), trimmed.strip_prefix("- ")) {
+            (Some(Value::List(items)), Some(item)) => items.push(unquote(item)),
+            (Some(Value::Text(text)), _) if block => {
+                if !text.is_empty() {
+                    text.push(' ');
+                }
+                text.push_str(trimmed);
+            }
+            _ => {}
+        }
+    }
+    Fields(fields)
+}
+
+/// the keys at the top of TOML, the tables are not read
+pub fn parse_toml(s: &str) -> Result<Fields, String> {
+    let table = toml::from_str::<toml::value::Table>(s).map_err(|e| e.to_string())?;
+    let text = |value: &toml::Value| match value {
+        toml::Value::String(s) => s.clone(),
+        value => value.to_string(),
+    };
+    let fields = table
+        .iter()
+        .filter_map(|(key, value)| match value {
+            toml::Value::Table(_) => None,
+            toml::Value::Array(items) => {
+                Some((key.clone(), Value::List(items.iter().map(text).collect())))
+            }
+            value => Some((key.clone(), Value::Text(text(value)))),
+        })
+        .collect();
+    Ok(Fields(fields))
+}
+
+/// the front matter and the body of file, the front matter is
+/// empty if the file has none
+pub fn split(s: &str) -> Result<(Fields, &str), String> {
+    let s = s.trim_start_matches('\u{feff}');
+    for (fence, yaml) in [("---", true), ("+++", false)] {
+        if !s.starts_with(fence) {
+            continue;
+        }
+        let rest = &s[fence.len()..];
+        let end = rest
+            .match_indices(&format!("\n{}", fence))
+            .map(|(pos, _)| pos)
+            .next()
+            .ok_or_else(|| format!("the front matter is not closed by {}", fence))?;
+        let matter = &rest[..end];
+        let body = &rest[end + fence.len() + 1..];
+        let fields = if yaml {
+            parse_yaml(matter)
+        } else {
+            parse_toml(matter)?
+        };
+        return Ok((fields, body));
+    }
+    if s.starts_with('{') {
+        return Err("the front matter in JSON is not supported".into());
+    }
+    Ok((Fields::default(), s))
+}
+
+#[test]
+fn test_front_matter() {
+    let (fields, body) = split(
+        "---\ntitle: \"Hello: World\"\ntags: [rust, \"web assembly\"]\ncategories:\n  - notes\n  - misc\ndraft: false\ndescription: >\n  a long\n  text\n---\nbody",
+    )
+    .unwrap();
+    assert_eq!(fields.text("title"), Some("Hello: World"));
+    assert_eq!(fields.list("tags"), vec!["rust", "web assembly"]);
+    assert_eq!(fields.list("categories"), vec!["notes", "misc"]);
+    assert_eq!(fields.flag("draft"), Some(false));
+    assert_eq!(fields.text("description"), Some("a long text"));
+    assert_eq!(body.trim(), "body");
+
+    let (fields, body) = split(
+        "+++\ntitle = \"Hi\"\ndate = 2021-10-10T13:32:00Z\ntags = [\"a\", \"b\"]\n[params]\nx = 1\n+++\nbody",
+    )
+    .unwrap();
+    assert_eq!(fields.text("title"), Some("Hi"));
+    assert_eq!(fields.text("date"), Some("2021-10-10T13:32:00Z"));
+    assert_eq!(fields.list("tags"), vec!["a", "b"]);
+    assert_eq!(body.trim(), "body");
+
+    let (fields, _) = split("no front matter").unwrap();
+    assert!(fields.0.is_empty());
+    assert!(split("---\ntitle: x\n").is_err());
+}
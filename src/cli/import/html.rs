@@ -0,0 +1,252 @@
+//! Here provide the converting of HTML into markdown, the elements
+//! markdown has no syntax for are kept as HTML and reported
+use super::Report;
+use crate::common::markup::{parse_html, Element, Node};
+use std::path::Path;
+
+/// the elements kept as they are
+const KEPT: &[&str] = &[
+    "table", "iframe", "video", "audio", "object", "embed", "form", "dl", "details", "svg", "math",
+];
+/// the elements dropped along with their content
+const DROPPED: &[&str] = &["script", "style", "noscript"];
+
+struct Converter<'a> {
+    source: &'a Path,
+    report: &'a mut Report,
+}
+
+/// the text escaped for markdown, the whitespaces are collapsed and the
+/// blank lines are kept as the breaks of paragraphs
+fn text(s: &str) -> String {
+    let space = regex::Regex::new(r"\s+").unwrap();
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('*', "\\*")
+        .replace('_', "\\_")
+        .replace('`', "\\`")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace('<', "&lt;");
+    space
+        .replace_all(&escaped, |caps: &regex::Captures| {
+            if caps[0].matches('\n').count() > 1 {
+                "\n\n"
+            } else {
+                " "
+            }
+        })
+        .to_string()
+}
+
+/// the inline text wrapped by the mark, the spaces around it are kept
+/// outside, eg: `<b> a </b>` is ` **a** `
+fn wrap(inner: &str, mark: &str) -> String {
+    let trimmed = inner.trim();
+    if trimmed.is_empty() {
+        return inner.to_string();
+    }
+    let lead = &inner[..inner.len() - inner.trim_start().len()];
+    let trail = &inner[inner.trim_end().len()..];
+    format!("{}{}{}{}{}", lead, mark, trimmed, mark, trail)
+}
+
+fn block(inner: &str) -> String {
+    format!("\n\n{}\n\n", inner.trim())
+}
+
+/// the lines prefixed, the first one by `first`, the blank lines are collapsed
+fn prefix(inner: &str, first: &str, rest: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for line in inner.trim().lines().map(str::trim_end) {
+        match (lines.is_empty(), line.is_empty()) {
+            (true, _) => lines.push(format!("{}{}", first, line)),
+            (false, true) if lines.last().map(String::as_str) == Some(rest.trim_end()) => {}
+            (false, true) => lines.push(rest.trim_end().to_string()),
+            (false, false) => lines.push(format!("{}{}", rest, line)),
+        }
+    }
+    lines.join("\n")
+}
+
+/// the language of code block, from the class, eg: `language-rust`
+/// or `brush: rust` of the plugins of WordPress
+fn language(element: &Element) -> String {
+    let pat = regex::Regex::new(r"(?:language-|lang-|brush:\s*)([\w+#-]+)").unwrap();
+    let class = format!(
+        "{} {}",
+        element.attr("class").unwrap_or_default(),
+        element.attr("lang").unwrap_or_default()
+    );
+    pat.captures(&class)
+        .map(|e| e[1].to_string())
+        .or_else(|| element.attr("lang").map(String::from))
+        .unwrap_or_default()
+}
+
+impl Converter<'_> {
+    fn children(&mut self, element: &Element) -> String {
+        let mut s = String::new();
+        for child in &element.children {
+            match child {
+                Node::Element(e) => s.push_str(&self.element(e)),
+                Node::Text(t) => s.push_str(&text(t)),
+                Node::Comment(c) if c.trim() == "more" => s.push_str(&block("<!--break-->")),
+                // the comments of the block editor, eg: `<!-- wp:paragraph -->`
+                Node::Comment(_) => {}
+            }
+        }
+        s
+    }
+
+    fn list(&mut self, element: &Element, ordered: bool) -> String {
+        let start = element
+            .attr("start")
+            .and_then(|e| e.parse().ok())
+            .unwrap_or(1);
+        let items = element
+            .all("li")
+            .enumerate()
+            .map(|(i, item)| {
+                let marker = match ordered {
+                    true => format!("{}. ", start + i),
+                    false => "- ".to_string(),
+                };
+                let indent = " ".repeat(marker.len());
+                prefix(&self.children(item), &marker, &indent)
+            })
+            .collect::<Vec<_>>();
+        block(&items.join("\n"))
+    }
+
+    fn element(&mut self, e: &Element) -> String {
+        match e.name.as_str() {
+            "p" | "div" | "section" | "article" | "header" | "footer" | "main" | "aside"
+            | "figure" | "center" => block(&self.children(e)),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = e.name[1..].parse().unwrap_or(1);
+                let inner = self
+                    .children(e)
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                block(&format!("{} {}", "#".repeat(level), inner))
+            }
+            "figcaption" => block(&wrap(&self.children(e), "*")),
+            "strong" | "b" => wrap(&self.children(e), "**"),
+            "em" | "i" | "cite" => wrap(&self.children(e), "*"),
+            "del" | "s" | "strike" => wrap(&self.children(e), "~~"),
+            "code" | "kbd" | "tt" => {
+                let code = e.text();
+                let fence = if code.contains('`') { "``" } else { "`" };
+                format!("{}{}{}", fence, code, fence)
+            }
+            "pre" => {
+                let code = e.text();
+                let fence = if code.contains("```") { "````" } else { "```" };
+                let lang = match language(e) {
+                    lang if lang.is_empty() => e.first("code").map(language).unwrap_or_default(),
+                    lang => lang,
+                };
+                block(&format!(
+                    "{}{}\n{}\n{}",
+                    fence,
+                    lang,
+                    code.trim_matches('\n'),
+                    fence
+                ))
+            }
+            "blockquote" => block(&prefix(&self.children(e), "> ", "> ")),
+            "ul" => self.list(e, false),
+            "ol" => self.list(e, true),
+            "br" => "\\\n".into(),
+            "hr" => block("***"),
+            "a" => {
+                let inner = self.children(e);
+                match (e.attr("href"), e.attr("title")) {
+                    (Some(href), _) if href.is_empty() || inner.trim().is_empty() => inner,
+                    (Some(href), Some(title)) => {
+                        format!(
+                            "[{}]({} \"{}\")",
+                            inner.trim(),
+                            href,
+                            title.replace('"', "'")
+                        )
+                    }
+                    (Some(href), None) => format!("[{}]({})", inner.trim(), href),
+                    (None, _) => inner,
+                }
+            }
+            "img" => {
+                let src = e.attr("src").unwrap_or_default();
+                let alt = e.attr("alt").unwrap_or_default().replace(['[', ']'], "");
+                match e.attr("title") {
+                    Some(title) => format!("![{}]({} \"{}\")", alt, src, title.replace('"', "'")),
+                    None => format!("![{}]({})", alt, src),
+                }
+            }
+            name if KEPT.contains(&name) => {
+                self.report
+                    .add(self.source, format!("<{}> is kept as HTML", name));
+                block(&e.html())
+            }
+            name if DROPPED.contains(&name) => {
+                self.report
+                    .add(self.source, format!("<{}> is dropped", name));
+                String::new()
+            }
+            // the others, eg: `<span>`, are left out with their content kept
+            _ => self.children(e),
+        }
+    }
+}
+
+/// the markdown of HTML
+pub fn to_markdown(html: &str, source: &Path, report: &mut Report) -> String {
+    let root = parse_html(html);
+    let markdown = Converter { source, report }.children(&root);
+    // the blank lines are collapsed, but those in the code blocks
+    let mut lines: Vec<&str> = Vec::new();
+    let mut fence = None;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start_matches([' ', '>']);
+        match fence {
+            Some(f) if trimmed.starts_with(f) => fence = None,
+            Some(_) => {}
+            None if trimmed.starts_with("```") => {
+                fence = Some(if trimmed.starts_with("````") {
+                    "````"
+                } else {
+                    "```"
+                })
+            }
+            None if line.trim().is_empty() && matches!(lines.last(), None | Some(&"")) => continue,
+            None if line.trim().is_empty() => {
+                lines.push("");
+                continue;
+            }
+            None => {}
+        }
+        lines.push(if fence.is_some() {
+            line
+        } else {
+            line.trim_end()
+        });
+    }
+    lines.join("\n").trim().to_string()
+}
+
+#[test]
+fn test_html() {
+    let mut report = Report::default();
+    let markdown = to_markdown(
+        "<!-- wp:paragraph --><p>Hello <strong>bold </strong>and <a href=\"/a\">link</a>, 2*3</p><!-- /wp:paragraph -->\n\n<!--more-->\n<h2>Title</h2><ul><li>one</li><li>two<ol><li>sub</li></ol></li></ul><pre class=\"brush: rust\">fn main() {\n\n    x &lt; 1\n}</pre><blockquote><p>a</p><p>b</p></blockquote><p><img src=\"a.png\" alt=\"A\"><br>line</p><table><tr><td>x</td></tr></table>",
+        Path::new("a.xml"),
+        &mut report,
+    );
+    assert_eq!(
+        markdown,
+        "Hello **bold** and [link](/a), 2\\*3\n\n<!--break-->\n\n## Title\n\n- one\n- two\n\n  1. sub\n\n```rust\nfn main() {\n\n    x < 1\n}\n```\n\n> a\n>\n> b\n\n![A](a.png)\\\nline\n\n<table><tr><td>x</td></tr></table>"
+    );
+    assert_eq!(report.problems.len(), 1);
+}
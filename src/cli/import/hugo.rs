@@ -0,0 +1,280 @@
+//! Here provide the reading of Hugo site, the posts in `content/posts/`,
+//! either single files or page bundles with `index.md` and their images
+use super::front_matter::split;
+use super::{files, image_urls, merge, parse_date, slug_of, Imported, Report, POST_LINK};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const EXTENSIONS: &[&str] = &["md", "markdown"];
+const SUMMARY_DIVIDER: &str = "<!--more-->";
+/// the sections posts are in
+const SECTIONS: &[&str] = &["content/posts", "content/post", "content/blog"];
+
+/// the arguments of shortcode, positional and named,
+/// eg: `src="a.png" title="A"` or `"go" "linenos=table"`
+fn arguments(s: &str) -> (Vec<String>, HashMap<String, String>) {
+    let pat = regex::Regex::new(
+        r#"(?:(?P<key>\w+)\s*=\s*)?(?:"(?P<dq>[^"]*)"|'(?P<sq>[^']*)'|(?P<bare>[^\s"']+))"#,
+    )
+    .unwrap();
+    let mut positional = Vec::new();
+    let mut named = HashMap::new();
+    for caps in pat.captures_iter(s) {
+        let value = ["dq", "sq", "bare"]
+            .iter()
+            .find_map(|e| caps.name(e))
+            .map_or("", |e| e.as_str())
+            .to_string();
+        match caps.name("key") {
+            Some(key) => {
+                named.insert(key.as_str().to_string(), value);
+            }
+            None => positional.push(value),
+        }
+    }
+    (positional, named)
+}
+
+/// the key of post that other posts link to it by, the name of file,
+/// or the name of directory for page bundles, followed by the language
+/// of translation, eg: "posts/next/index.fr.md" -> "next.fr"
+fn key_of(path: &str) -> String {
+    let path = path.trim_end_matches('/');
+    let (dir, name) = path.rsplit_once('/').unwrap_or(("", path));
+    let name = EXTENSIONS
+        .iter()
+        .find_map(|e| name.strip_suffix(&format!(".{}", e)))
+        .unwrap_or(name);
+    let (name, lang) = name.split_once('.').unwrap_or((name, ""));
+    let name = match name {
+        "index" => dir.rsplit('/').next().unwrap_or(dir),
+        name => name,
+    };
+    match lang {
+        "" => name.to_string(),
+        lang => format!("{}.{}", name, lang),
+    }
+}
+
+/// convert the shortcodes known into markdown, the unknown ones are reported
+fn shortcodes(body: &str, source: &Path, report: &mut Report) -> String {
+    let pat =
+        regex::Regex::new(r"\{\{[<%]\s*(?P<close>/?)\s*(?P<name>[\w-]+)(?P<args>.*?)\s*[>%]\}\}")
+            .unwrap();
+    let body = pat.replace_all(body, |caps: &regex::Captures| {
+        let (positional, named) = arguments(&caps["args"]);
+        let arg = |key: &str, pos: usize| {
+            named
+                .get(key)
+                .or_else(|| positional.get(pos))
+                .cloned()
+                .unwrap_or_default()
+        };
+        match (&caps["close"], &caps["name"]) {
+            ("", "highlight") => format!("```{}", arg("lang", 0)),
+            ("/", "highlight") => "```".to_string(),
+            ("", "figure") => {
+                let alt = named
+                    .get("alt")
+                    .or_else(|| named.get("caption"))
+                    .or_else(|| named.get("title"))
+                    .cloned()
+                    .unwrap_or_default();
+                match named.get("title") {
+                    Some(title) => format!("![{}]({} \"{}\")", alt, arg("src", 0), title),
+                    None => format!("![{}]({})", alt, arg("src", 0)),
+                }
+            }
+            ("", "ref") | ("", "relref") => format!("{}{}", POST_LINK, key_of(&arg("path", 0))),
+            ("", "youtube") => format!(
+                "[YouTube video](https://www.youtube.com/watch?v={})",
+                arg("id", 0)
+            ),
+            ("", "vimeo") => format!("[Vimeo video](https://vimeo.com/{})", arg("id", 0)),
+            ("", "gist") => format!(
+                "[gist](https://gist.github.com/{}/{})",
+                arg("user", 0),
+                arg("id", 1)
+            ),
+            ("", "tweet") if positional.len() > 1 || named.contains_key("user") => format!(
+                "[tweet](https://twitter.com/{}/status/{})",
+                arg("user", 0),
+                arg("id", 1)
+            ),
+            _ => {
+                report.add(source, format!("shortcode not converted: {}", &caps[0]));
+                caps[0].to_string()
+            }
+        }
+    });
+    body.to_string()
+}
+
+fn convert(dir: &Path, file: &Path, report: &mut Report) -> Option<Imported> {
+    let text = match fs::read_to_string(file) {
+        Ok(text) => text,
+        Err(e) => {
+            report.add(file, format!("not read, {}", e));
+            return None;
+        }
+    };
+    let (fields, body) = match split(&text) {
+        Ok(split) => split,
+        Err(e) => {
+            report.add(file, format!("not imported, {}", e));
+            return None;
+        }
+    };
+    // the language is in the name of file, eg: `post.fr.md`
+    let stem = file.file_stem()?.to_string_lossy().to_string();
+    let (name, lang) = match stem.split_once('.') {
+        Some((name, lang)) => (name.to_string(), lang.to_string()),
+        None => (stem.clone(), String::new()),
+    };
+    // the page bundle is named after the directory
+    let name = if name == "index" {
+        file.parent()?.file_name()?.to_string_lossy().to_string()
+    } else {
+        name
+    };
+    let date = ["date", "publishDate", "lastmod"]
+        .iter()
+        .find_map(|key| fields.text(key).and_then(parse_date));
+    let date = match date {
+        Some(date) => date,
+        None => {
+            report.add(file, "not imported, `date` is missing in front matter");
+            return None;
+        }
+    };
+    let title = fields
+        .text("title")
+        .map(String::from)
+        .unwrap_or_else(|| name.replace('-', " "));
+    let body = shortcodes(body, file, report).replacen(SUMMARY_DIVIDER, "<!--break-->", 1);
+
+    let mut images = Vec::new();
+    for url in image_urls(&body) {
+        let path = url.split(['?', '#']).next().unwrap_or_default();
+        let image: PathBuf = match path.strip_prefix('/') {
+            Some(path) => dir.join("static").join(path),
+            None => file.parent().unwrap_or(dir).join(path),
+        };
+        if image.is_file() {
+            images.push((url, image));
+        } else {
+            report.add(file, format!("image not found: {}", url));
+        }
+    }
+
+    let mut aliases = fields.items("aliases");
+    aliases.extend(fields.text("url").map(String::from));
+    let slug = slug_of(fields.text("slug").unwrap_or(&name), &title);
+    // the translations are named apart, and share the name as the key of translation
+    let (key, slug, translation) = match lang.as_str() {
+        "" => (name.clone(), slug, String::new()),
+        lang => (
+            format!("{}.{}", name, lang),
+            format!("{}-{}", slug, lang),
+            name.clone(),
+        ),
+    };
+    Some(Imported {
+        source: file.to_path_buf(),
+        key,
+        slug,
+        title,
+        date,
+        published: fields.flag("draft") != Some(true),
+        tags: merge(vec![fields.list("tags"), fields.list("categories")]),
+        authors: merge(vec![fields.items("authors"), fields.items("author")]),
+        aliases,
+        lang: fields.text("lang").map(String::from).unwrap_or(lang),
+        translation,
+        body,
+        images,
+    })
+}
+
+/// the posts of the site in `dir`
+pub fn read(dir: &Path, report: &mut Report) -> Vec<Imported> {
+    let section = match SECTIONS.iter().map(|e| dir.join(e)).find(|e| e.is_dir()) {
+        Some(section) => section,
+        None => {
+            report.add(dir, format!("none of {} found", SECTIONS.join(", ")));
+            return vec![];
+        }
+    };
+    let mut posts = files(&section, EXTENSIONS)
+        .iter()
+        .filter(|e| !e.to_string_lossy().contains("/_index."))
+        .filter_map(|file| convert(dir, file, report))
+        .collect::<Vec<_>>();
+    // the post in the default language is a translation of the others
+    let translated = posts
+        .iter()
+        .map(|e| e.translation.clone())
+        .filter(|e| !e.is_empty())
+        .collect::<HashSet<_>>();
+    for post in posts.iter_mut() {
+        if translated.contains(&post.key) {
+            post.translation = post.key.clone();
+        }
+    }
+    posts
+}
+
+#[test]
+fn test_hugo() {
+    let mut report = Report::default();
+    let body = shortcodes(
+        "{{< highlight go \"linenos=table\" >}}\nfunc main() {}\n{{< /highlight >}}\n{{< figure src=\"a.png\" alt=\"A\" >}}\n[next]({{< ref \"posts/next/index.md\" >}})\n{{< youtube w7Ft2ymGmfc >}}\n{{< unknown x >}}",
+        Path::new("a.md"),
+        &mut report,
+    );
+    assert_eq!(
+        body,
+        "```go\nfunc main() {}\n```\n![A](a.png)\n[next](noer-post:next)\n[YouTube video](https://www.youtube.com/watch?v=w7Ft2ymGmfc)\n{{< unknown x >}}"
+    );
+    assert_eq!(report.problems.len(), 1);
+    assert_eq!(key_of("post.md"), "post");
+    assert_eq!(key_of("post.fr.md"), "post.fr");
+    assert_eq!(key_of("posts/next/index.md"), "next");
+    assert_eq!(key_of("posts/next/index.fr.md"), "next.fr");
+
+    let dir = crate::cli::test_dir("hugo");
+    let posts = dir.join("content/posts");
+    fs::create_dir_all(&posts).unwrap();
+    fs::write(
+        posts.join("hello.md"),
+        "---\ntitle: Hello\ndate: 2021-10-10\n---\nhi",
+    )
+    .unwrap();
+    fs::write(
+        posts.join("hello.fr.md"),
+        "---\ntitle: Bonjour\ndate: 2021-10-10\n---\nsalut",
+    )
+    .unwrap();
+    let mut imported = read(&dir, &mut report);
+    imported.sort_by(|a, b| a.key.cmp(&b.key));
+    let names = imported
+        .iter()
+        .map(|e| {
+            (
+                e.key.as_str(),
+                e.slug.as_str(),
+                e.lang.as_str(),
+                e.translation.as_str(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            ("hello", "hello", "", "hello"),
+            ("hello.fr", "hello-fr", "fr", "hello")
+        ]
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
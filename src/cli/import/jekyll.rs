@@ -0,0 +1,165 @@
+//! Here provide the reading of Jekyll site, the posts in `_posts/`
+//! named like `2021-10-10-title.md` and the drafts in `_drafts/`
+use super::front_matter::split;
+use super::{files, image_urls, merge, parse_date, slug_of, Imported, Report, POST_LINK};
+use std::fs;
+use std::path::Path;
+
+const EXTENSIONS: &[&str] = &["md", "markdown", "html"];
+const EXCERPT_SEPARATOR: &str = "<!--more-->";
+
+/// the key of post that other posts link to it by, the name of file
+/// eg: `{% post_url 2021-10-10-title %}`
+fn key_of(name: &str) -> String {
+    let name = name.trim().trim_matches(|c| c == '"' || c == '\'');
+    let name = name.rsplit('/').next().unwrap_or(name);
+    name.rsplit_once('.')
+        .filter(|(_, ext)| EXTENSIONS.contains(ext))
+        .map_or(name, |(stem, _)| stem)
+        .to_string()
+}
+
+/// convert the Liquid tags known into markdown, the unknown ones are reported
+fn liquid(body: &str, source: &Path, report: &mut Report) -> String {
+    let replaces = [
+        (r"\{%-?\s*highlight\s+(\w+)[^%]*-?%\}", "```$1"),
+        (r"\{%-?\s*endhighlight\s*-?%\}", "```"),
+        (r"\{%-?\s*(raw|endraw)\s*-?%\}", ""),
+        (r"\{\{-?\s*site\.(baseurl|url)\s*-?\}\}", ""),
+        (
+            r#"\{\{-?\s*["']([^"']+)["']\s*\|\s*(relative_url|absolute_url)\s*-?\}\}"#,
+            "$1",
+        ),
+    ];
+    let mut body = body.to_string();
+    for (pat, rep) in replaces.iter() {
+        body = regex::Regex::new(pat)
+            .unwrap()
+            .replace_all(&body, *rep)
+            .to_string();
+    }
+    let link = regex::Regex::new(r"\{%-?\s*(post_url|link)\s+(?P<name>\S+)\s*-?%\}").unwrap();
+    body = link
+        .replace_all(&body, |caps: &regex::Captures| {
+            format!("{}{}", POST_LINK, key_of(&caps["name"]))
+        })
+        .to_string();
+    let unknown = regex::Regex::new(r"\{%.*?%\}|\{\{[^}]*(site\.|page\.|\|)[^}]*\}\}").unwrap();
+    for tag in unknown.find_iter(&body) {
+        report.add(source, format!("Liquid not converted: {}", tag.as_str()));
+    }
+    body
+}
+
+fn convert(dir: &Path, file: &Path, draft: bool, report: &mut Report) -> Option<Imported> {
+    let text = match fs::read_to_string(file) {
+        Ok(text) => text,
+        Err(e) => {
+            report.add(file, format!("not read, {}", e));
+            return None;
+        }
+    };
+    let (fields, body) = match split(&text) {
+        Ok(split) => split,
+        Err(e) => {
+            report.add(file, format!("not imported, {}", e));
+            return None;
+        }
+    };
+    let stem = file.file_stem()?.to_string_lossy().to_string();
+    let pat = regex::Regex::new(r"^(?P<date>\d{4}-\d{1,2}-\d{1,2})-(?P<name>.+)$").unwrap();
+    let (name_date, name) = match pat.captures(&stem) {
+        Some(caps) => (parse_date(&caps["date"]), caps["name"].to_string()),
+        None => (None, stem.clone()),
+    };
+    let date = fields.text("date").and_then(parse_date).or(name_date);
+    let date = match (date, draft) {
+        (Some(date), _) => date,
+        (None, true) => crate::cli::now(),
+        (None, false) => {
+            report.add(
+                file,
+                "not imported, the date is neither in file name nor in front matter",
+            );
+            return None;
+        }
+    };
+    let title = fields
+        .text("title")
+        .map(String::from)
+        .unwrap_or_else(|| name.replace('-', " "));
+    let separator = fields
+        .text("excerpt_separator")
+        .unwrap_or(EXCERPT_SEPARATOR);
+    let body = liquid(body, file, report).replacen(separator, "<!--break-->", 1);
+    if file.extension() == Some("html".as_ref()) {
+        report.add(file, "the body in HTML is kept as it is");
+    }
+
+    let mut images = Vec::new();
+    for url in image_urls(&body) {
+        let path = url.split(['?', '#']).next().unwrap_or_default();
+        let image = match path.strip_prefix('/') {
+            Some(path) => dir.join(path),
+            None => file.parent().unwrap_or(dir).join(path),
+        };
+        if image.is_file() {
+            images.push((url, image));
+        } else {
+            report.add(file, format!("image not found: {}", url));
+        }
+    }
+
+    let mut aliases = fields.items("redirect_from");
+    aliases.extend(fields.text("permalink").map(String::from));
+    Some(Imported {
+        source: file.to_path_buf(),
+        key: stem,
+        slug: slug_of(fields.text("slug").unwrap_or(&name), &title),
+        title,
+        date,
+        published: !draft && fields.flag("published") != Some(false),
+        tags: merge(vec![
+            fields.list("tags"),
+            fields.list("tag"),
+            fields.list("categories"),
+            fields.list("category"),
+        ]),
+        authors: merge(vec![fields.items("authors"), fields.items("author")]),
+        aliases,
+        lang: fields.text("lang").unwrap_or_default().to_string(),
+        translation: String::new(),
+        body,
+        images,
+    })
+}
+
+/// the posts and drafts of the site in `dir`
+pub fn read(dir: &Path, report: &mut Report) -> Vec<Imported> {
+    if !dir.join("_posts").is_dir() {
+        report.add(dir, "_posts/ not found, it may not be a Jekyll site");
+    }
+    let mut posts = Vec::new();
+    for (sub, draft) in [("_posts", false), ("_drafts", true)] {
+        for file in files(&dir.join(sub), EXTENSIONS) {
+            posts.extend(convert(dir, &file, draft, report));
+        }
+    }
+    posts
+}
+
+#[test]
+fn test_jekyll() {
+    let mut report = Report::default();
+    let body = liquid(
+        "{% highlight rust linenos %}\nfn main() {}\n{% endhighlight %}\n[next]({% post_url 2021-10-10-next %})\n![a]({{ site.baseurl }}/assets/a.png)\n{% include note.html %}",
+        Path::new("a.md"),
+        &mut report,
+    );
+    assert_eq!(
+        body,
+        "```rust\nfn main() {}\n```\n[next](noer-post:2021-10-10-next)\n![a](/assets/a.png)\n{% include note.html %}"
+    );
+    assert_eq!(report.problems.len(), 1);
+    assert_eq!(key_of("_posts/2021-10-10-next.md"), "2021-10-10-next");
+}
@@ -0,0 +1,351 @@
+//! `noer import`, convert the posts of other blogs into noer's
+//! the converters read the source into `Imported`, which is written
+//! into `posts/` with the images copied into `assets/`
+mod front_matter;
+mod html;
+mod hugo;
+mod jekyll;
+mod wordpress;
+
+use super::index::insert_by_date;
+use super::{load_config, post_file, read_index, write_index, Args, POSTS_DIR, SITE_CONFIG};
+use crate::common::content::slugify;
+use crate::common::date;
+use crate::common::front_matter::parse_blog;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// the scheme of links to other imported posts, eg: "noer-post:<key>",
+/// they are made into the routes once all posts are named
+pub const POST_LINK: &str = "noer-post:";
+const ASSETS_DIR: &str = "assets";
+
+/// a post read from the source
+#[derive(Debug, Default)]
+pub struct Imported {
+    /// the file it is read from
+    pub source: PathBuf,
+    /// what the links of other posts refer to it by
+    pub key: String,
+    pub title: String,
+    pub slug: String,
+    /// [year, month, day, hour, minute, second]
+    pub date: [u64; 6],
+    pub published: bool,
+    pub tags: Vec<String>,
+    pub authors: Vec<String>,
+    pub aliases: Vec<String>,
+    pub lang: String,
+    /// the key shared with its translations, empty if it has none
+    pub translation: String,
+    /// the markdown, the summary is followed by `<!--break-->`
+    pub body: String,
+    /// the local images, the url in body and the file
+    pub images: Vec<(String, PathBuf)>,
+}
+
+/// the problems found, reported once the import is done
+#[derive(Default)]
+pub struct Report {
+    pub problems: Vec<(String, String)>,
+}
+
+impl Report {
+    pub fn add(&mut self, source: &Path, message: impl Into<String>) {
+        self.problems
+            .push((source.display().to_string(), message.into()));
+    }
+}
+
+/// [year, month, day, hour, minute, second] of the date in text,
+/// eg: "2021-10-10 13:32:00 +0800", "2021-10-10T13:32:00Z",
+/// None unless it has a four-digit year, a month and a day
+pub fn parse_date(s: &str) -> Option<[u64; 6]> {
+    let items = s
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|e| !e.is_empty())
+        .collect::<Vec<_>>();
+    if items.len() < 3 || items[0].len() != 4 {
+        return None;
+    }
+    Some(date::parse_date(s))
+}
+
+/// the items merged without duplicates, in the order they come
+pub fn merge(lists: impl IntoIterator<Item = Vec<String>>) -> Vec<String> {
+    let mut items = Vec::new();
+    for item in lists.into_iter().flatten() {
+        if !item.is_empty() && !items.contains(&item) {
+            items.push(item);
+        }
+    }
+    items
+}
+
+/// the local images in markdown or html, `![alt](url)` or `<img src="url">`
+pub fn image_urls(body: &str) -> Vec<String> {
+    let markdown =
+        regex::Regex::new(r#"!\[[^\]]*\]\(\s*<?([^)\s>]+)>?(\s+"[^"]*")?\s*\)"#).unwrap();
+    let html = regex::Regex::new(r#"<img[^>]*\ssrc\s*=\s*["']([^"']+)["']"#).unwrap();
+    let scheme = regex::Regex::new(r"^([a-zA-Z][a-zA-Z0-9+.-]*:|//)").unwrap();
+    let mut urls = Vec::new();
+    for caps in markdown.captures_iter(body).chain(html.captures_iter(body)) {
+        let url = caps[1].to_string();
+        if !scheme.is_match(&url) && !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+    urls
+}
+
+/// the front matter and body of post in noer
+fn render(post: &Imported) -> String {
+    let mut s = format!(
+        "---\ntitle: {}\npublished: {}\n",
+        post.title.replace('\n', " "),
+        post.published
+    );
+    if !post.tags.is_empty() {
+        s.push_str(&format!("tags: {}\n", post.tags.join(", ")));
+    }
+    if !post.authors.is_empty() {
+        s.push_str(&format!("authors: {}\n", post.authors.join(", ")));
+    }
+    if !post.lang.is_empty() {
+        s.push_str(&format!("lang: {}\n", post.lang));
+    }
+    if !post.translation.is_empty() {
+        s.push_str(&format!("translation: {}\n", post.translation));
+    }
+    if !post.aliases.is_empty() {
+        s.push_str("aliases:\n");
+        for alias in &post.aliases {
+            s.push_str(&format!(" - {}\n", alias));
+        }
+    }
+    s.push_str("---\n\n");
+    s.push_str(post.body.trim());
+    s.push('\n');
+    s
+}
+
+/// copy the image into `assets/<slug>/`, the url of it in site is returned
+fn copy_image(file: &Path, slug: &str, base: &str) -> Result<String, String> {
+    let name = file
+        .file_name()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let dir = Path::new(ASSETS_DIR).join(slug);
+    fs::create_dir_all(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let target = dir.join(&name);
+    if !target.exists() {
+        fs::copy(file, &target).map_err(|e| format!("{}: {}", file.display(), e))?;
+    }
+    Ok(format!("{}{}/{}/{}", base, ASSETS_DIR, slug, name))
+}
+
+/// write the posts into `posts/` and add them to the index,
+/// the number of posts written is returned
+pub fn write_posts(posts: Vec<Imported>, base: &str, report: &mut Report) -> Result<usize, String> {
+    // name the posts first, so the links among them are known
+    let mut named = Vec::new();
+    let mut routes = HashMap::new();
+    for post in posts {
+        let (path, meta) = match post_file(post.date, &post.slug) {
+            Ok(file) => file,
+            Err(e) => {
+                report.add(&post.source, format!("not imported, {}", e));
+                continue;
+            }
+        };
+        if Path::new(&path).exists() || named.iter().any(|(e, _)| *e == path) {
+            report.add(
+                &post.source,
+                format!("not imported, {} already exists", path),
+            );
+            continue;
+        }
+        routes.insert(
+            post.key.clone(),
+            format!("{}posts/{}/{}", base, meta.id, meta.title),
+        );
+        named.push((path, post));
+    }
+
+    let link = regex::Regex::new(&format!(r"{}(?P<key>[^)\s\x22']+)", POST_LINK)).unwrap();
+    let mut index = read_index();
+    let mut written = 0;
+    for (path, mut post) in named {
+        let mut missing = Vec::new();
+        post.body = link
+            .replace_all(&post.body, |caps: &regex::Captures| {
+                match routes.get(&caps["key"]) {
+                    Some(route) => route.clone(),
+                    None => {
+                        missing.push(caps["key"].to_string());
+                        caps[0].to_string()
+                    }
+                }
+            })
+            .to_string();
+        for key in missing {
+            report.add(
+                &post.source,
+                format!("the post linked is not imported: {}", key),
+            );
+        }
+        for (url, file) in std::mem::take(&mut post.images) {
+            match copy_image(&file, &post.slug, base) {
+                Ok(local) => post.body = post.body.replace(&url, &local),
+                Err(e) => report.add(&post.source, format!("image not copied, {}", e)),
+            }
+        }
+
+        let content = render(&post);
+        let meta = match crate::common::content::BlogMeta::from_path(&path) {
+            Some(meta) => meta,
+            None => continue,
+        };
+        if let Err(e) = parse_blog(&content, &meta) {
+            report.add(&post.source, format!("not imported, {}", e));
+            continue;
+        }
+        fs::write(&path, content).map_err(|e| format!("{}: {}", path, e))?;
+        println!("{} -> {}", post.source.display(), path);
+        insert_by_date(&mut index, path);
+        written += 1;
+    }
+    write_index(&index)?;
+    Ok(written)
+}
+
+/// the files with the extensions in the directory and its sub-directories
+pub fn files(dir: &Path, extensions: &[&str]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for path in fs::read_dir(&dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+        {
+            let ext = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or_default();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if extensions.contains(&ext) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// the slug of post, from the title if the name is not given
+pub fn slug_of(name: &str, title: &str) -> String {
+    match slugify(name) {
+        slug if slug.is_empty() => slugify(title),
+        slug => slug,
+    }
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &[])?;
+    args.check(&["uploads"])?;
+    let (kind, source) = match args.values.as_slice() {
+        [kind, source] => (kind.as_str(), Path::new(source)),
+        _ => return Err("usage: noer import <jekyll|hugo|wordpress> <dir|file>".into()),
+    };
+    if !Path::new(POSTS_DIR).is_dir() {
+        return Err(format!(
+            "{} not found, run it in the root of site",
+            POSTS_DIR
+        ));
+    }
+    match kind {
+        "wordpress" if !source.is_file() => {
+            return Err(format!("{} is not a file", source.display()))
+        }
+        "jekyll" | "hugo" if !source.is_dir() => {
+            return Err(format!("{} is not a directory", source.display()))
+        }
+        _ => {}
+    }
+    let (config, errors) = load_config();
+    for e in errors {
+        eprintln!("warning: {}: {}", SITE_CONFIG, e);
+    }
+    let base = match config.subpath.as_str() {
+        "/" => "/".to_string(),
+        subpath => format!("/{}", subpath),
+    };
+
+    let mut report = Report::default();
+    let posts = match kind {
+        "jekyll" => jekyll::read(source, &mut report),
+        "hugo" => hugo::read(source, &mut report),
+        "wordpress" => {
+            // the uploads directory is copied next to the export by default
+            let uploads = match args.option("uploads") {
+                Some(uploads) => PathBuf::from(uploads),
+                None => source.with_file_name("uploads"),
+            };
+            wordpress::read(source, &uploads, &mut report)
+        }
+        kind => {
+            return Err(format!(
+                "unknown kind `{}`, expected jekyll, hugo or wordpress",
+                kind
+            ))
+        }
+    };
+    let total = posts.len();
+    let written = write_posts(posts, &base, &mut report)?;
+    for (source, message) in &report.problems {
+        println!("{}: {}", source, message);
+    }
+    println!(
+        "{} of {} posts imported, {} problems",
+        written,
+        total,
+        report.problems.len()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_import() {
+    assert_eq!(
+        parse_date("2021-10-10 13:32:05 +0800"),
+        Some([2021, 10, 10, 13, 32, 5])
+    );
+    assert_eq!(
+        parse_date("2021-10-10T13:32Z"),
+        Some([2021, 10, 10, 13, 32, 0])
+    );
+    assert_eq!(parse_date("10/10"), None);
+    assert_eq!(
+        image_urls("![a](/img/a.png) ![b](https://x.io/b.png \"t\") <img alt=\"c\" src=\"c.jpg\">"),
+        vec!["/img/a.png", "c.jpg"]
+    );
+    let post = Imported {
+        title: "Hello: World".into(),
+        published: true,
+        tags: vec!["rust".into()],
+        aliases: vec!["/2021/10/10/hello.html".into()],
+        translation: "hello".into(),
+        body: "summary\n<!--break-->\nrest".into(),
+        ..Default::default()
+    };
+    let meta = crate::common::content::BlogMeta::from_path("posts/2021-10-10-hello.rmd").unwrap();
+    let blog = parse_blog(&render(&post), &meta).unwrap();
+    assert_eq!(blog.tags, vec!["rust"]);
+    assert_eq!(blog.aliases, vec!["/2021/10/10/hello.html"]);
+    assert_eq!(blog.translation, "hello");
+    assert_eq!(blog.content, vec!["summary", "rest"]);
+}
@@ -0,0 +1,351 @@
+//! Here provide the reading of WordPress export (WXR), the posts and drafts
+//! in it, with the attachments found in the copy of uploads directory
+use super::html::to_markdown;
+use super::{merge, parse_date, slug_of, Imported, Report, POST_LINK};
+use crate::common::markup::{decode, parse_xml, Element};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const UPLOADS: &str = "wp-content/uploads/";
+/// the shortcodes that can't be converted, they are reported
+const SHORTCODES: &[&str] = &[
+    "gallery",
+    "audio",
+    "video",
+    "playlist",
+    "wpvideo",
+    "contact-form",
+    "googlemaps",
+];
+
+/// whether the post of status is published, None for the ones skipped,
+/// eg: "trash" or "auto-draft"
+fn published(status: &str) -> Option<bool> {
+    match status {
+        "publish" => Some(true),
+        "draft" | "pending" | "future" | "private" => Some(false),
+        _ => None,
+    }
+}
+
+/// the name of post decoded, eg: "%e4%bd%a0%e5%a5%bd" is "你好"
+fn percent_decode(s: &str) -> String {
+    let mut bytes = Vec::new();
+    let mut rest = s.as_bytes();
+    while let Some((&b, after)) = rest.split_first() {
+        let hex = after
+            .get(..2)
+            .and_then(|e| std::str::from_utf8(e).ok())
+            .and_then(|e| u8::from_str_radix(e, 16).ok());
+        match (b, hex) {
+            (b'%', Some(hex)) => {
+                bytes.push(hex);
+                rest = &after[2..];
+            }
+            _ => {
+                bytes.push(b);
+                rest = after;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// the path of url in the site, eg: "/2021/10/10/hello/" of
+/// "https://example.com/2021/10/10/hello/", None if it is on other sites
+fn local_path<'a>(url: &'a str, site: &str) -> Option<&'a str> {
+    match url.strip_prefix(site) {
+        Some(path) if path.starts_with('/') => Some(path),
+        Some("") => Some("/"),
+        Some(_) => None,
+        None if url.starts_with('/') && !url.starts_with("//") => Some(url),
+        None => None,
+    }
+}
+
+/// convert the shortcodes known into HTML, the unknown ones are reported
+fn shortcodes(content: &str, source: &Path, report: &mut Report) -> String {
+    let caption = regex::Regex::new(r"(?s)\[caption[^\]]*\](.*?)\[/caption\]").unwrap();
+    let figure =
+        regex::Regex::new(r"(?s)^\s*(<a[^>]*>\s*<img[^>]*>\s*</a>|<img[^>]*>)(.*)$").unwrap();
+    let content = caption.replace_all(content, |caps: &regex::Captures| {
+        match figure.captures(&caps[1]) {
+            Some(e) => format!(
+                "<figure>{}<figcaption>{}</figcaption></figure>",
+                &e[1],
+                e[2].trim()
+            ),
+            None => caps[1].to_string(),
+        }
+    });
+    let embed = regex::Regex::new(r"\[embed[^\]]*\]\s*(\S+?)\s*\[/embed\]").unwrap();
+    let content = embed.replace_all(&content, r#"<a href="$1">$1</a>"#);
+    let code = regex::Regex::new(
+        r#"(?s)\[(?:sourcecode|code)(?:[^\]]*?\b(?:language|lang)=["']?([\w+#-]+)["']?)?[^\]]*\](.*?)\[/(?:sourcecode|code)\]"#,
+    )
+    .unwrap();
+    let content = code.replace_all(&content, |caps: &regex::Captures| {
+        let lang = caps.get(1).map_or("", |e| e.as_str());
+        // the code is kept raw by the plugins, unless it is escaped already
+        let code = match caps[2].contains("&lt;") || caps[2].contains("&amp;") {
+            true => caps[2].to_string(),
+            false => caps[2].replace('&', "&amp;").replace('<', "&lt;"),
+        };
+        format!("<pre class=\"brush: {}\">{}</pre>", lang, code)
+    });
+    let unknown = regex::Regex::new(r"\[/?([a-z][\w-]*)[^\]\n]*\]").unwrap();
+    for caps in unknown.captures_iter(&content) {
+        if SHORTCODES.contains(&&caps[1]) && !caps[0].starts_with("[/") {
+            report.add(source, format!("shortcode not converted: {}", &caps[0]));
+        }
+    }
+    content.to_string()
+}
+
+/// the url of the uploads directory, eg: "https://example.com/wp-content/uploads/",
+/// of the attachment url, which is followed by `<year>/<month>/<name>`
+fn uploads_url(url: &str) -> Option<&str> {
+    let pat = regex::Regex::new(r"^(.*/)\d{4}/\d{2}/[^/]+$").unwrap();
+    pat.captures(url)
+        .and_then(|e| e.get(1))
+        .map(|e| &url[..e.end()])
+}
+
+/// the file of url in the uploads directory, the original one is used
+/// if the resized one, eg: `a-300x200.png`, is not there
+fn uploaded(url: &str, prefixes: &[String], uploads: &Path) -> Option<std::path::PathBuf> {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let relative = match path.find(UPLOADS) {
+        Some(pos) => &path[pos + UPLOADS.len()..],
+        None => prefixes
+            .iter()
+            .find_map(|e| path.strip_prefix(e.as_str()))?,
+    };
+    let file = uploads.join(percent_decode(relative));
+    let resized = regex::Regex::new(r"-\d+x\d+(\.\w+)$").unwrap();
+    let original = uploads.join(percent_decode(&resized.replace(relative, "$1")));
+    match file.is_file() {
+        true => Some(file),
+        false => Some(original).filter(|e| e.is_file()),
+    }
+}
+
+/// the urls in markdown, the links, images and those in HTML
+fn urls(markdown: &str) -> Vec<String> {
+    let pat = regex::Regex::new(r#"\]\(\s*<?([^)\s>]+)|\s(?:src|href)\s*=\s*"([^"]+)""#).unwrap();
+    let mut urls = Vec::new();
+    for caps in pat.captures_iter(markdown) {
+        let url = caps.get(1).or_else(|| caps.get(2)).unwrap().as_str();
+        if !urls.iter().any(|e| e == url) {
+            urls.push(url.to_string());
+        }
+    }
+    urls
+}
+
+struct Export<'a> {
+    file: &'a Path,
+    uploads: &'a Path,
+    /// the url of site, eg: "https://example.com"
+    site: String,
+    /// the display names of authors by their logins
+    authors: HashMap<String, String>,
+    /// the urls of the uploads directory, of the attachments
+    prefixes: Vec<String>,
+}
+
+impl Export<'_> {
+    fn convert(&self, item: &Element, report: &mut Report) -> Option<Imported> {
+        let id = item.child_text("wp:post_id");
+        let title = decode(item.child_text("title").trim());
+        let source = Path::new(&format!("{}#{}", self.file.display(), id)).to_path_buf();
+        let published = published(&item.child_text("wp:status"))?;
+        let date = match parse_date(&item.child_text("wp:post_date")) {
+            Some(date) if date[0] > 0 => date,
+            _ if !published => crate::cli::now(),
+            _ => {
+                report.add(&source, "not imported, the date is missing");
+                return None;
+            }
+        };
+
+        let content = shortcodes(&item.child_text("content:encoded"), &source, report);
+        let body = to_markdown(&content, &source, report);
+        let mut images = Vec::new();
+        for url in urls(&body) {
+            let attachment =
+                url.contains(UPLOADS) || self.prefixes.iter().any(|e| url.starts_with(e));
+            if !attachment {
+                continue;
+            }
+            match uploaded(&url, &self.prefixes, self.uploads) {
+                Some(file) => images.push((url, file)),
+                None if self.uploads.is_dir() => {
+                    report.add(&source, format!("attachment not found: {}", url))
+                }
+                None => {}
+            }
+        }
+
+        let (mut categories, mut tags) = (Vec::new(), Vec::new());
+        for category in item.all("category") {
+            let name = decode(category.text().trim());
+            match (category.attr("domain"), category.attr("nicename")) {
+                (Some("category"), Some("uncategorized")) => {}
+                (Some("category"), _) => categories.push(name),
+                (Some("post_tag"), _) => tags.push(name),
+                _ => {}
+            }
+        }
+        let creator = item.child_text("dc:creator");
+        let author = self.authors.get(&creator).cloned().unwrap_or(creator);
+        let link = item.child_text("link");
+        let aliases = local_path(&link, &self.site)
+            .filter(|e| *e != "/" && !e.contains('?'))
+            .map(String::from)
+            .into_iter()
+            .collect();
+        let name = percent_decode(&item.child_text("wp:post_name"));
+        Some(Imported {
+            source,
+            key: id,
+            slug: slug_of(&name, &title),
+            title,
+            date,
+            published,
+            tags: merge(vec![categories, tags]),
+            authors: merge(vec![vec![author]]),
+            aliases,
+            lang: String::new(),
+            translation: String::new(),
+            body,
+            images,
+        })
+    }
+}
+
+/// the posts and drafts in the export `file`, the attachments are looked
+/// for in `uploads`, the copy of `wp-content/uploads/`
+pub fn read(file: &Path, uploads: &Path, report: &mut Report) -> Vec<Imported> {
+    let root = match fs::read_to_string(file).map(|e| parse_xml(&e)) {
+        Ok(Ok(root)) => root,
+        Ok(Err(e)) => {
+            report.add(file, format!("not read, {}", e));
+            return vec![];
+        }
+        Err(e) => {
+            report.add(file, format!("not read, {}", e));
+            return vec![];
+        }
+    };
+    let channel = match root.first("rss").and_then(|e| e.first("channel")) {
+        Some(channel) => channel,
+        None => {
+            report.add(
+                file,
+                "<channel> not found, it may not be a WordPress export",
+            );
+            return vec![];
+        }
+    };
+    let site = match channel.child_text("wp:base_blog_url") {
+        url if url.is_empty() => channel.child_text("link"),
+        url => url,
+    };
+    let authors = channel
+        .all("wp:author")
+        .map(|e| {
+            (
+                e.child_text("wp:author_login"),
+                e.child_text("wp:author_display_name"),
+            )
+        })
+        .filter(|(_, name)| !name.is_empty())
+        .collect();
+    let items = channel.all("item").collect::<Vec<_>>();
+    let mut prefixes = Vec::new();
+    for item in items
+        .iter()
+        .filter(|e| e.child_text("wp:post_type") == "attachment")
+    {
+        let url = item.child_text("wp:attachment_url");
+        if let Some(prefix) = uploads_url(&url) {
+            if !prefixes.iter().any(|e| e == prefix) {
+                prefixes.push(prefix.to_string());
+            }
+        }
+    }
+    if !prefixes.is_empty() && !uploads.is_dir() {
+        report.add(
+            uploads,
+            "not found, the attachments are not copied, use --uploads <dir>",
+        );
+    }
+    let export = Export {
+        file,
+        uploads,
+        site: site.trim_end_matches('/').to_string(),
+        authors,
+        prefixes,
+    };
+
+    let posts = items
+        .iter()
+        .filter(|e| e.child_text("wp:post_type") == "post")
+        .collect::<Vec<_>>();
+    // the permalinks and the short links, eg: "/?p=12", of the posts
+    let mut links = HashMap::new();
+    for item in &posts {
+        let id = item.child_text("wp:post_id");
+        for url in [item.child_text("link"), item.child_text("guid")] {
+            if let Some(path) = local_path(&url, &export.site) {
+                links.insert(path.trim_end_matches('/').to_string(), id.clone());
+            }
+        }
+        links.insert(format!("/?p={}", id), id);
+    }
+    let link = regex::Regex::new(r"\]\(([^)\s]+)").unwrap();
+    posts
+        .into_iter()
+        .filter_map(|item| export.convert(item, report))
+        .map(|mut post| {
+            post.body = link
+                .replace_all(&post.body, |caps: &regex::Captures| {
+                    let path = local_path(&caps[1], &export.site).map(|e| e.trim_end_matches('/'));
+                    match path.and_then(|e| links.get(e)) {
+                        Some(key) => format!("]({}{}", POST_LINK, key),
+                        None => caps[0].to_string(),
+                    }
+                })
+                .to_string();
+            post
+        })
+        .collect()
+}
+
+#[test]
+fn test_wordpress() {
+    assert_eq!(percent_decode("%e4%bd%a0%e5%a5%bd-world"), "你好-world");
+    assert_eq!(
+        local_path("https://a.io/2021/10/10/hi/", "https://a.io"),
+        Some("/2021/10/10/hi/")
+    );
+    assert_eq!(local_path("https://b.io/x", "https://a.io"), None);
+    assert_eq!(
+        uploads_url("https://a.io/wp-content/uploads/2021/10/a.png"),
+        Some("https://a.io/wp-content/uploads/")
+    );
+
+    let mut report = Report::default();
+    let html = shortcodes(
+        "[caption id=\"c\" width=\"300\"]<img src=\"a.png\" /> A cat[/caption]\n[sourcecode language=\"python\"]if a < b: pass[/sourcecode]\n[gallery ids=\"1,2\"]",
+        Path::new("a.xml"),
+        &mut report,
+    );
+    assert_eq!(
+        html,
+        "<figure><img src=\"a.png\" /><figcaption>A cat</figcaption></figure>\n<pre class=\"brush: python\">if a &lt; b: pass</pre>\n[gallery ids=\"1,2\"]"
+    );
+    assert_eq!(report.problems.len(), 1);
+}
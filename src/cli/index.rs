@@ -0,0 +1,75 @@
+//! Here provide the indexes generated from the posts,
+//! the site reads them instead of listing the directory
+use super::{list_posts, read_index};
+use crate::common::content::{Blog, BlogMeta};
+use crate::common::front_matter::parse_blog;
+use std::fs;
+use std::path::Path;
+
+fn timestamp(path: &str) -> u64 {
+    BlogMeta::from_path(path).map_or(0, |e| e.timestamp)
+}
+
+/// the paths of posts in `markdown.index` that exist, the posts
+/// not listed are put among them in order of date, the latest first
+pub fn markdown_index() -> Vec<String> {
+    let mut index = read_index();
+    index.retain(|e| Path::new(e).is_file());
+    let mut unlisted = list_posts()
+        .into_iter()
+        .filter(|e| !index.contains(e) && BlogMeta::from_path(e).is_some())
+        .collect::<Vec<_>>();
+    unlisted.sort_by_key(|e| std::cmp::Reverse(timestamp(e)));
+    for path in unlisted {
+        insert_by_date(&mut index, path);
+    }
+    index
+}
+
+/// put the path before the first post older than it
+pub fn insert_by_date(index: &mut Vec<String>, path: String) {
+    let time = timestamp(&path);
+    let pos = index
+        .iter()
+        .position(|e| timestamp(e) < time)
+        .unwrap_or(index.len());
+    index.insert(pos, path);
+}
+
+/// the posts parsed, the invalid ones are left out
+pub fn load_blogs(posts: &[String]) -> Vec<Blog> {
+    posts
+        .iter()
+        .filter_map(|path| {
+            let meta = BlogMeta::from_path(path)?;
+            let text = fs::read_to_string(path).ok()?;
+            parse_blog(&text, &meta).ok()
+        })
+        .collect()
+}
+
+/// the redirect table from the aliases in front matter,
+/// each line is `<alias> <path of post>`
+pub fn redirects_index(blogs: &[Blog]) -> Vec<(String, String)> {
+    blogs
+        .iter()
+        .flat_map(|blog| {
+            let path = blog.meta.path.to_string_lossy().to_string();
+            blog.aliases
+                .iter()
+                .map(move |alias| (alias.clone(), path.clone()))
+        })
+        .collect()
+}
+
+/// the content of index files, one item per line
+pub fn to_lines(index: &[String]) -> String {
+    index.iter().map(|e| format!("{}\n", e)).collect()
+}
+
+pub fn redirects_to_lines(redirects: &[(String, String)]) -> String {
+    redirects
+        .iter()
+        .map(|(alias, path)| format!("{} {}\n", alias, path))
+        .collect()
+}
@@ -0,0 +1,253 @@
+//! Here provide the command line tools to manage the site,
+//! they are built for the native target, eg: `cargo run -- new "My Title"`
+mod build;
+mod check;
+mod epub;
+mod feed;
+mod import;
+mod index;
+mod new;
+mod serve;
+mod websocket;
+mod zip;
+
+use crate::common::content::BlogMeta;
+use crate::config::SiteConfig;
+use std::fs;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const POSTS_DIR: &str = "posts";
+const INDEX: &str = "posts/markdown.index";
+/// the redirect table generated from the aliases
+const REDIRECTS: &str = "posts/redirects.index";
+const SITE_CONFIG: &str = "site.toml";
+
+const USAGE: &str = "noer, the tools to manage the blog
+
+Usage: noer <command> [options]
+
+Commands:
+    new <title> [--tags a,b] [--draft] [--archetype file]
+        create a post with the title in posts/ and add it to the index
+    check [--json]
+        validate the posts and the index, exit with 1 if any error is found
+    serve [--dir dist] [--host 127.0.0.1] [--port 8080]
+        serve the built site under the sub-path, the pages are reloaded
+        once the posts, the assets or site.toml are changed
+    build [--out dist] [--gitpage]
+        build the site into the output directory, docs/ with --gitpage,
+        along with the indexes, the feed, 404.html and the redirects
+    import <jekyll|hugo> <dir>
+        convert the posts of the Jekyll or Hugo site in the directory,
+        the images referred are copied into assets/
+    import wordpress <file> [--uploads <dir>]
+        convert the posts and drafts of the WordPress export, the
+        attachments are copied from the uploads directory
+    export [--tag <tag> | --series <name>] [--title <title>] [--out <file>]
+        export the published posts, or those with the tag or in the
+        series, into an EPUB book, a chapter per post
+    help
+        print this message";
+
+/// the command line arguments that are not positional
+pub struct Args {
+    /// the positional ones
+    pub values: Vec<String>,
+    /// the options, `--key value` or `--flag` with empty value
+    pub options: Vec<(String, String)>,
+}
+
+impl Args {
+    /// the flags listed take no value
+    pub fn parse(args: &[String], flags: &[&str]) -> Result<Self, String> {
+        let mut values = Vec::new();
+        let mut options = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.strip_prefix("--") {
+                Some(key) if flags.contains(&key) => options.push((key.into(), "".into())),
+                Some(key) => match iter.next() {
+                    Some(value) => options.push((key.into(), value.clone())),
+                    None => return Err(format!("`--{}` requires a value", key)),
+                },
+                None => values.push(arg.clone()),
+            }
+        }
+        Ok(Self { values, options })
+    }
+
+    pub fn option(&self, key: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn flag(&self, key: &str) -> bool {
+        self.options.iter().any(|(k, _)| k == key)
+    }
+
+    /// the options not in `known` are errors
+    pub fn check(&self, known: &[&str]) -> Result<(), String> {
+        match self
+            .options
+            .iter()
+            .find(|(k, _)| !known.contains(&k.as_str()))
+        {
+            Some((key, _)) => Err(format!("unknown option `--{}`", key)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// the paths of posts in the index, in the order they are listed
+fn read_index() -> Vec<String> {
+    fs::read_to_string(INDEX)
+        .unwrap_or_default()
+        .lines()
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty())
+        .collect()
+}
+
+fn write_index(index: &[String]) -> Result<(), String> {
+    fs::write(INDEX, index::to_lines(index)).map_err(|e| format!("{} not updated: {}", INDEX, e))
+}
+
+/// the paths of posts in `posts/`, as they are in the index
+fn list_posts() -> Vec<String> {
+    fs::read_dir(POSTS_DIR)
+        .map(|dir| {
+            dir.filter_map(|e| e.ok())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .filter(|e| e.ends_with(".rmd"))
+                .map(|e| format!("{}/{}", POSTS_DIR, e))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// the posts the tests run on, they do not change with the posts of the site,
+/// the latest first as in `markdown.index`
+#[cfg(test)]
+fn fixture_posts() -> Vec<String> {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/posts");
+    let mut posts = fs::read_dir(dir)
+        .unwrap()
+        .map(|e| format!("{}/{}", dir, e.unwrap().file_name().to_string_lossy()))
+        .collect::<Vec<_>>();
+    posts.sort_by(|a, b| b.cmp(a));
+    posts
+}
+
+/// an empty directory for the test, it is not shared with other tests or runs
+#[cfg(test)]
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("noer-{}-test-{}", name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+/// the site config in `site.toml` and the problems found in it,
+/// the defaults are used if it does not exist
+fn load_config() -> (SiteConfig, Vec<String>) {
+    match fs::read_to_string(SITE_CONFIG) {
+        Ok(text) => SiteConfig::from_toml(&text),
+        Err(_) => {
+            let mut config = SiteConfig::default();
+            config.validate();
+            (config, vec![])
+        }
+    }
+}
+
+/// the date and time of unix timestamp in UTC,
+/// [year, month, day, hour, minute, second]
+fn civil(secs: u64) -> [u64; 6] {
+    let days = (secs / 86400) as i64;
+    let rem = secs % 86400;
+    // the days since 0000-03-01, so the leap day is the last of year
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    [
+        year as u64,
+        month as u64,
+        day as u64,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60,
+    ]
+}
+
+/// the path of post, the time is left out if `with_time` is false
+fn post_path(date: [u64; 6], slug: &str, with_time: bool) -> String {
+    let time = if with_time {
+        format!("-{:02}-{:02}", date[3], date[4])
+    } else {
+        "".into()
+    };
+    format!(
+        "{}/{:04}-{:02}-{:02}{}-{}.rmd",
+        POSTS_DIR, date[0], date[1], date[2], time, slug
+    )
+}
+
+/// the path of post named after the date and the slug, and its meta
+/// the numbers leading the slug are taken as the time,
+/// so the time is left out if the slug is not kept, or it is midnight
+fn post_file(date: [u64; 6], slug: &str) -> Result<(String, BlogMeta), String> {
+    [true, false]
+        .iter()
+        .filter(|with_time| !**with_time || date[3] + date[4] > 0)
+        .map(|with_time| post_path(date, slug, *with_time))
+        .find_map(|path| match BlogMeta::from_path(&path) {
+            Some(meta) if meta.title == slug => Some((path, meta)),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            format!(
+                "the title {:?} is not kept in the file name, do not start it with numbers",
+                slug
+            )
+        })
+}
+
+/// the current date and time in UTC
+fn now() -> [u64; 6] {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|e| e.as_secs())
+        .unwrap_or_default();
+    civil(secs)
+}
+
+/// run the command in arguments, exit with 1 if failed
+pub fn run() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let result = match args.first().map(|e| e.as_str()) {
+        Some("new") => new::run(&args[1..]),
+        Some("check") => check::run(&args[1..]),
+        Some("serve") => serve::run(&args[1..]),
+        Some("build") => build::run(&args[1..]),
+        Some("import") => import::run(&args[1..]),
+        Some("export") => epub::run(&args[1..]),
+        Some("help") | Some("--help") | Some("-h") | None => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(command) => Err(format!("unknown command `{}`\n\n{}", command, USAGE)),
+    };
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,90 @@
+//! `noer new`, create a post named after the time and the title
+//! the post is made from the archetype and added to the index
+use super::{now, post_file, read_index, write_index, Args, POSTS_DIR};
+use crate::common::content::slugify;
+use crate::common::front_matter::str2blog;
+use std::fs;
+use std::path::Path;
+
+/// the template of post, the placeholders `{{title}}`, `{{published}}`
+/// and `{{tags}}` are filled
+const ARCHETYPE: &str = "archetypes/post.rmd";
+/// the archetype used if the site does not have one
+const DEFAULT_ARCHETYPE: &str = include_str!("../../archetypes/post.rmd");
+
+/// fill the placeholders of archetype
+fn render(archetype: &str, title: &str, tags: &[String], draft: bool) -> String {
+    archetype
+        .replace("{{title}}", title)
+        .replace("{{published}}", if draft { "false" } else { "true" })
+        .replace("{{tags}}", &tags.join(", "))
+}
+
+/// add the path to the top of index, the latest post first
+fn add_to_index(path: &str) -> Result<(), String> {
+    let mut index = read_index();
+    index.insert(0, path.to_string());
+    write_index(&index)
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &["draft"])?;
+    args.check(&["tags", "draft", "archetype"])?;
+    let title = args.values.join(" ");
+    let slug = slugify(&title);
+    if slug.is_empty() {
+        return Err("the title of post is required, eg: noer new \"My Title\"".into());
+    }
+    if !Path::new(POSTS_DIR).is_dir() {
+        return Err(format!(
+            "{} not found, run it in the root of site",
+            POSTS_DIR
+        ));
+    }
+    let tags = args
+        .option("tags")
+        .unwrap_or_default()
+        .split(',')
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty())
+        .collect::<Vec<_>>();
+    let archetype = match args.option("archetype") {
+        Some(file) => fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?,
+        None => fs::read_to_string(ARCHETYPE).unwrap_or_else(|_| DEFAULT_ARCHETYPE.into()),
+    };
+
+    let (path, meta) = post_file(now(), &slug)?;
+    if Path::new(&path).exists() {
+        return Err(format!("{} already exists", path));
+    }
+
+    let content = render(&archetype, &title, &tags, args.flag("draft"));
+    if str2blog(&content, &meta).is_none() {
+        return Err("the archetype shall have `title` and `published` in front matter".into());
+    }
+    fs::write(&path, content).map_err(|e| format!("{} not created: {}", path, e))?;
+    add_to_index(&path)?;
+    println!("created {}", path);
+    Ok(())
+}
+
+#[test]
+fn test_new() {
+    use crate::common::content::BlogMeta;
+    // 2022-03-11 08:05:09
+    let now = super::civil(1646985909);
+    assert_eq!(now, [2022, 3, 11, 8, 5, 9]);
+    assert_eq!(super::civil(951782400), [2000, 2, 29, 0, 0, 0]);
+    let path = super::post_path(now, "my-title", true);
+    assert_eq!(path, "posts/2022-03-11-08-05-my-title.rmd");
+    assert_eq!(BlogMeta::from_path(&path).unwrap().title, "my-title");
+    // the leading numbers of title are taken as the time
+    let path = super::post_path(now, "10-tips", true);
+    assert_ne!(BlogMeta::from_path(&path).unwrap().title, "10-tips");
+    assert!(post_file(now, "10-tips").is_err());
+
+    let content = render(DEFAULT_ARCHETYPE, "My Title", &["rust".into()], true);
+    let blog = str2blog(&content, &BlogMeta::from_path(&path).unwrap()).unwrap();
+    assert!(!blog.published);
+    assert_eq!(blog.tags, vec!["rust"]);
+}
@@ -0,0 +1,397 @@
+//! `noer serve`, serve the built site under the sub-path for development
+//! the posts, the assets and `site.toml` are served from the source,
+//! so they are not built again once changed, and the pages opened
+//! are reloaded over websocket
+use super::index::{load_blogs, markdown_index, redirects_index, redirects_to_lines, to_lines};
+use super::{load_config, websocket, Args, INDEX, REDIRECTS, SITE_CONFIG};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// the path of websocket that the pages listen to for changes
+const LIVE_RELOAD: &str = "__noer/livereload";
+/// the source watched and served in place of the built ones
+const WATCHED: &[&str] = &["posts", "assets", SITE_CONFIG];
+const POLL_MS: u64 = 500;
+
+/// the websockets of the pages opened
+type Clients = Arc<Mutex<Vec<TcpStream>>>;
+
+struct Site {
+    /// the output of build
+    dir: PathBuf,
+    /// "/" or "sub-path/"
+    subpath: String,
+    /// the indexes generated by path, they are cleared once the posts change
+    generated: Mutex<HashMap<String, String>>,
+}
+
+impl Site {
+    /// the url of the root, eg: "/noer/"
+    fn base(&self) -> String {
+        match self.subpath.as_str() {
+            "/" => "/".into(),
+            subpath => format!("/{}", subpath),
+        }
+    }
+
+    /// the index at path, generated unless it is kept
+    fn generated(&self, path: &str, generate: impl FnOnce() -> String) -> String {
+        let mut generated = self.generated.lock().unwrap();
+        generated
+            .entry(path.to_string())
+            .or_insert_with(generate)
+            .clone()
+    }
+}
+
+/// what the path in site is served with
+enum Source {
+    /// the index generated from the posts
+    Generated(String),
+    File(PathBuf),
+    /// `index.html` for the routes of the app
+    Page,
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+    {
+        "html" => "text/html; charset=utf-8",
+        "js" => "application/javascript",
+        "wasm" => "application/wasm",
+        "css" => "text/css",
+        "json" => "application/json",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        _ => "text/plain; charset=utf-8",
+    }
+}
+
+/// decode the `%xx` in url
+fn decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|e| std::str::from_utf8(e).ok())
+            .and_then(|e| u8::from_str_radix(e, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// the source of the path relative to the root of site, the paths
+/// out of it, eg: "../secret" or "/etc/passwd", are not served
+fn resolve(site: &Site, path: &str) -> Option<Source> {
+    if Path::new(path)
+        .components()
+        .any(|e| !matches!(e, Component::Normal(_)))
+    {
+        return None;
+    }
+    match path {
+        INDEX => {
+            let index = site.generated(path, || to_lines(&markdown_index()));
+            return Some(Source::Generated(index));
+        }
+        REDIRECTS => {
+            let redirects = site.generated(path, || {
+                redirects_to_lines(&redirects_index(&load_blogs(&markdown_index())))
+            });
+            return Some(Source::Generated(redirects));
+        }
+        "" | "index.html" => return Some(Source::Page),
+        _ => {}
+    }
+    let watched = WATCHED
+        .iter()
+        .any(|e| path == *e || path.starts_with(&format!("{}/", e)));
+    if watched && Path::new(path).is_file() {
+        return Some(Source::File(path.into()));
+    }
+    let file = site.dir.join(path);
+    if file.is_file() {
+        return Some(Source::File(file));
+    }
+    // the routes of app, eg: "posts/123/title"
+    if Path::new(path).extension().is_none() {
+        return Some(Source::Page);
+    }
+    None
+}
+
+/// `index.html` with the sub-path in `<base>` and the live reload script
+fn page(site: &Site) -> io::Result<String> {
+    let html = fs::read_to_string(site.dir.join("index.html"))?;
+    let base = format!("<base href=\"{}\" />", site.base());
+    let pat = regex::Regex::new(r"<base[^>]*>").unwrap();
+    let html = if pat.is_match(&html) {
+        pat.replace(&html, base.as_str()).to_string()
+    } else {
+        html.replacen("<head>", &format!("<head>{}", base), 1)
+    };
+    let script = format!(
+        r#"<script>
+  new WebSocket((location.protocol === "https:" ? "wss://" : "ws://") + location.host + "{}{}").onmessage = function (e) {{
+    console.log("noer serve: " + e.data);
+    location.reload();
+  }};
+</script>"#,
+        site.base(),
+        LIVE_RELOAD
+    );
+    Ok(match html.rfind("</body>") {
+        Some(pos) => format!("{}{}{}", &html[..pos], script, &html[pos..]),
+        None => html + &script,
+    })
+}
+
+fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    headers: &[(&str, String)],
+    body: &[u8],
+    head: bool,
+) -> io::Result<()> {
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n",
+        status,
+        body.len()
+    );
+    for (key, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", key, value));
+    }
+    response.push_str("\r\n");
+    stream.write_all(response.as_bytes())?;
+    if !head {
+        stream.write_all(body)?;
+    }
+    stream.flush()
+}
+
+/// the etag of file from its time of modification and size,
+/// so the cache of app is revalidated once it is changed
+fn etag(path: &Path) -> Option<String> {
+    let meta = fs::metadata(path).ok()?;
+    let modified = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some(format!("\"{:x}-{:x}\"", modified.as_millis(), meta.len()))
+}
+
+fn handle(mut stream: TcpStream, site: &Site, clients: &Clients) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut items = line.split_whitespace();
+    let (method, target) = match (items.next(), items.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target.to_string()),
+        _ => return Ok(()),
+    };
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            headers.insert(key.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let path = decode(target.split('?').next().unwrap_or_default());
+    let base = site.base();
+    if path == format!("{}{}", base, LIVE_RELOAD) {
+        let key = headers
+            .get("sec-websocket-key")
+            .cloned()
+            .unwrap_or_default();
+        websocket::handshake(&mut stream, &key)?;
+        clients.lock().unwrap().push(stream);
+        return Ok(());
+    }
+    if base != "/" && (path == "/" || path == base.trim_end_matches('/')) {
+        return respond(
+            &mut stream,
+            "301 Moved Permanently",
+            &[("Location", base)],
+            b"",
+            false,
+        );
+    }
+    let head = method == "HEAD";
+    if !head && method != "GET" {
+        return respond(&mut stream, "405 Method Not Allowed", &[], b"", false);
+    }
+    // the files built without the sub-path are served from the root
+    let rel = path
+        .strip_prefix(&base)
+        .unwrap_or(&path)
+        .trim_start_matches('/');
+    match resolve(site, rel) {
+        Some(Source::Generated(text)) => {
+            let headers = [("Content-Type", content_type(Path::new(rel)).to_string())];
+            respond(&mut stream, "200 OK", &headers, text.as_bytes(), head)
+        }
+        Some(Source::Page) => {
+            let html = page(site)?;
+            let headers = [("Content-Type", "text/html; charset=utf-8".to_string())];
+            respond(&mut stream, "200 OK", &headers, html.as_bytes(), head)
+        }
+        Some(Source::File(file)) => {
+            let tag = etag(&file).unwrap_or_default();
+            if !tag.is_empty() && headers.get("if-none-match") == Some(&tag) {
+                return respond(&mut stream, "304 Not Modified", &[("ETag", tag)], b"", true);
+            }
+            let response = [
+                ("Content-Type", content_type(&file).to_string()),
+                ("ETag", tag),
+            ];
+            respond(&mut stream, "200 OK", &response, &fs::read(&file)?, head)
+        }
+        None => respond(&mut stream, "404 Not Found", &[], b"not found", head),
+    }
+}
+
+/// the time of modification and size of the files watched
+fn snapshot() -> HashMap<PathBuf, (SystemTime, u64)> {
+    let mut files = HashMap::new();
+    let mut dirs = WATCHED.iter().map(PathBuf::from).collect::<Vec<_>>();
+    while let Some(path) = dirs.pop() {
+        let meta = match fs::metadata(&path) {
+            Ok(meta) => meta,
+            Err(_) => continue,
+        };
+        if meta.is_dir() {
+            if let Ok(dir) = fs::read_dir(&path) {
+                dirs.extend(dir.filter_map(|e| e.ok()).map(|e| e.path()));
+            }
+        } else if let Ok(modified) = meta.modified() {
+            files.insert(path, (modified, meta.len()));
+        }
+    }
+    files
+}
+
+/// poll the files watched and tell the pages what are changed,
+/// the indexes are generated again once asked for
+fn watch(site: &Site, clients: Clients) {
+    let mut last = snapshot();
+    loop {
+        thread::sleep(Duration::from_millis(POLL_MS));
+        let current = snapshot();
+        let mut changed = current
+            .iter()
+            .filter(|(path, stat)| last.get(*path) != Some(stat))
+            .map(|(path, _)| path)
+            .chain(last.keys().filter(|e| !current.contains_key(*e)))
+            .map(|e| e.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        last = current;
+        if changed.is_empty() {
+            continue;
+        }
+        changed.sort();
+        site.generated.lock().unwrap().clear();
+        let message = format!("changed {}", changed.join(" "));
+        println!("{}", message);
+        // the closed pages fail to be sent to
+        clients
+            .lock()
+            .unwrap()
+            .retain(|e| websocket::send_text(&mut &*e, &message).is_ok());
+    }
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &[])?;
+    args.check(&["port", "host", "dir"])?;
+    let (config, errors) = load_config();
+    for e in errors {
+        eprintln!("warning: {}: {}", SITE_CONFIG, e);
+    }
+    let site = Site {
+        dir: args.option("dir").unwrap_or("dist").into(),
+        subpath: config.subpath,
+        generated: Mutex::default(),
+    };
+    if !site.dir.join("index.html").is_file() {
+        return Err(format!(
+            "{} not found, build the site first",
+            site.dir.join("index.html").display()
+        ));
+    }
+    let addr = format!(
+        "{}:{}",
+        args.option("host").unwrap_or("127.0.0.1"),
+        args.option("port").unwrap_or("8080")
+    );
+    let listener = TcpListener::bind(&addr).map_err(|e| format!("{}: {}", addr, e))?;
+    println!(
+        "serving {} at http://{}{}",
+        site.dir.display(),
+        addr,
+        site.base()
+    );
+
+    let clients = Clients::default();
+    let site = Arc::new(site);
+    let (watched, watching) = (clients.clone(), site.clone());
+    thread::spawn(move || watch(&watching, watched));
+    for stream in listener.incoming().filter_map(|e| e.ok()) {
+        let site = site.clone();
+        let clients = clients.clone();
+        thread::spawn(move || {
+            if let Err(e) = handle(stream, &site, &clients) {
+                eprintln!("error: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+#[test]
+fn test_serve() {
+    assert_eq!(decode("/noer/posts/a%20b.rmd"), "/noer/posts/a b.rmd");
+    assert_eq!(decode("100%"), "100%");
+    let site = Site {
+        dir: "dist".into(),
+        subpath: "noer/".into(),
+        generated: Mutex::default(),
+    };
+    assert_eq!(site.base(), "/noer/");
+    assert!(resolve(&site, "../secret").is_none());
+    assert!(resolve(&site, "/etc/passwd").is_none());
+    assert!(matches!(resolve(&site, INDEX), Some(Source::Generated(_))));
+    // the index kept is served until the posts change
+    let kept = site.generated(INDEX, String::new);
+    assert!(matches!(resolve(&site, INDEX), Some(Source::Generated(e)) if e == kept));
+    assert!(matches!(
+        resolve(&site, "posts/1/title"),
+        Some(Source::Page)
+    ));
+    assert!(resolve(&site, "missing.js").is_none());
+}
@@ -0,0 +1,124 @@
+//! Here provide the writing of zip archive, the files are stored without
+//! compression, as the `mimetype` of EPUB is required to be
+use super::civil;
+
+/// a file written, its name, crc32, size and the offset of its header
+struct Entry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+pub struct Zip {
+    data: Vec<u8>,
+    entries: Vec<Entry>,
+    /// the modified time of files in MS-DOS format, (time, date)
+    modified: (u16, u16),
+}
+
+/// the crc32 of bytes, as zip requires
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+    for (i, item) in table.iter_mut().enumerate() {
+        let mut c = i as u32;
+        for _ in 0..8 {
+            c = if c & 1 == 1 {
+                0xEDB8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+        }
+        *item = c;
+    }
+    !bytes.iter().fold(!0u32, |crc, b| {
+        table[((crc ^ *b as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+impl Zip {
+    /// the files are modified at `secs` since unix epoch
+    pub fn new(secs: u64) -> Self {
+        let [year, month, day, hour, minute, second] = civil(secs);
+        let time = (hour << 11 | minute << 5 | (second / 2)) as u16;
+        let date = ((year.max(1980) - 1980) << 9 | month << 5 | day) as u16;
+        Self {
+            data: Vec::new(),
+            entries: Vec::new(),
+            modified: (time, date),
+        }
+    }
+
+    /// the header shared by the local and central ones, from the version needed
+    fn common_header(&self, crc: u32, size: u32, name: &str) -> Vec<u8> {
+        let mut h = Vec::new();
+        h.extend_from_slice(&10u16.to_le_bytes());
+        // the names are in utf-8
+        h.extend_from_slice(&0x0800u16.to_le_bytes());
+        // stored
+        h.extend_from_slice(&0u16.to_le_bytes());
+        h.extend_from_slice(&self.modified.0.to_le_bytes());
+        h.extend_from_slice(&self.modified.1.to_le_bytes());
+        h.extend_from_slice(&crc.to_le_bytes());
+        h.extend_from_slice(&size.to_le_bytes());
+        h.extend_from_slice(&size.to_le_bytes());
+        h.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        // no extra field
+        h.extend_from_slice(&0u16.to_le_bytes());
+        h
+    }
+
+    pub fn add(&mut self, name: &str, bytes: &[u8]) {
+        let entry = Entry {
+            name: name.to_string(),
+            crc: crc32(bytes),
+            size: bytes.len() as u32,
+            offset: self.data.len() as u32,
+        };
+        self.data.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        let header = self.common_header(entry.crc, entry.size, name);
+        self.data.extend_from_slice(&header);
+        self.data.extend_from_slice(name.as_bytes());
+        self.data.extend_from_slice(bytes);
+        self.entries.push(entry);
+    }
+
+    /// the archive with the central directory
+    pub fn finish(mut self) -> Vec<u8> {
+        let start = self.data.len() as u32;
+        for entry in &self.entries {
+            let mut h = 0x0201_4b50u32.to_le_bytes().to_vec();
+            // made by version 2.0
+            h.extend_from_slice(&20u16.to_le_bytes());
+            h.extend(self.common_header(entry.crc, entry.size, &entry.name));
+            // comment length, disk number, internal and external attributes
+            h.extend_from_slice(&[0; 10]);
+            h.extend_from_slice(&entry.offset.to_le_bytes());
+            h.extend_from_slice(entry.name.as_bytes());
+            self.data.extend(h);
+        }
+        let size = self.data.len() as u32 - start;
+        let count = self.entries.len() as u16;
+        self.data.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        self.data.extend_from_slice(&[0; 4]);
+        self.data.extend_from_slice(&count.to_le_bytes());
+        self.data.extend_from_slice(&count.to_le_bytes());
+        self.data.extend_from_slice(&size.to_le_bytes());
+        self.data.extend_from_slice(&start.to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes());
+        self.data
+    }
+}
+
+#[test]
+fn test_zip() {
+    assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    let mut zip = Zip::new(0);
+    zip.add("mimetype", b"application/epub+zip");
+    let data = zip.finish();
+    // the name and the content of the first file are at fixed offsets
+    assert_eq!(&data[..4], b"PK\x03\x04");
+    assert_eq!(&data[30..38], b"mimetype");
+    assert_eq!(&data[38..58], b"application/epub+zip");
+    assert_eq!(&data[data.len() - 22..data.len() - 18], b"PK\x05\x06");
+}
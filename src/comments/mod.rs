@@ -0,0 +1,105 @@
+//! Here provide the comments of posts
+//! the comments are loaded by a `CommentProvider` chosen in site config,
+//! so that other providers can be plugged in besides the static file
+pub mod static_file;
+
+use crate::config::SiteConfig;
+use crate::fetch::FetchError;
+use crate::BlogMeta;
+use serde::Deserialize;
+use std::future::Future;
+use std::pin::Pin;
+
+/// a comment of the post, it is a reply if `parent` is set
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Comment {
+    pub id: String,
+    #[serde(default)]
+    pub parent: Option<String>,
+    pub author: String,
+    /// website of the author
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub date: String,
+    /// markdown
+    pub body: String,
+}
+
+/// a comment along with its replies
+#[derive(Clone, Debug, PartialEq)]
+pub struct Thread {
+    pub comment: Comment,
+    pub replies: Vec<Thread>,
+}
+
+pub type CommentFuture = Pin<Box<dyn Future<Output = Result<Vec<Comment>, FetchError>>>>;
+
+/// the source of comments
+pub trait CommentProvider {
+    /// load all comments of the post
+    fn load(&self, meta: &BlogMeta) -> CommentFuture;
+}
+
+/// the provider in site config, None if comments are disabled
+pub fn provider(config: &SiteConfig) -> Option<Box<dyn CommentProvider>> {
+    match config.comments.provider.as_str() {
+        "static" => Some(Box::new(static_file::StaticFile::new(&config.comments.dir))),
+        _ => None,
+    }
+}
+
+/// organize the comments into threads, the reply whose parent is not
+/// found, or whose parents never reach a thread, eg: a cycle, is made a thread,
+/// each comment is shown once even if the ids are duplicated
+pub fn threads(comments: Vec<Comment>) -> Vec<Thread> {
+    fn build(
+        parent: usize,
+        comments: &[Comment],
+        parents: &[Option<usize>],
+        shown: &mut [bool],
+    ) -> Vec<Thread> {
+        let mut replies = Vec::new();
+        for (i, comment) in comments.iter().enumerate() {
+            if parents[i] == Some(parent) && !shown[i] {
+                shown[i] = true;
+                replies.push(Thread {
+                    comment: comment.clone(),
+                    replies: build(i, comments, parents, shown),
+                });
+            }
+        }
+        replies
+    }
+    // the parent of comment, the first one of the id
+    let parents = comments
+        .iter()
+        .map(|e| {
+            let parent = e.parent.as_ref()?;
+            comments.iter().position(|c| &c.id == parent)
+        })
+        .collect::<Vec<_>>();
+    // the parents reach a comment without parent in fewer steps than comments
+    let rooted = |mut i: usize| {
+        for _ in 0..comments.len() {
+            match parents[i] {
+                Some(parent) => i = parent,
+                None => return true,
+            }
+        }
+        false
+    };
+    let mut shown = vec![false; comments.len()];
+    let mut threads = Vec::new();
+    for (i, comment) in comments.iter().enumerate() {
+        if shown[i] || (parents[i].is_some() && rooted(i)) {
+            continue;
+        }
+        shown[i] = true;
+        threads.push(Thread {
+            comment: comment.clone(),
+            replies: build(i, &comments, &parents, &mut shown),
+        });
+    }
+    threads
+}
@@ -0,0 +1,59 @@
+//! comments read from a file per post in the repository,
+//! they are moderated before merged, eg: accepted via pull requests
+//! the file is `<dir>/<file name of post without extension>.json`
+//! which contains a list of comments
+use super::{Comment, CommentFuture, CommentProvider};
+use crate::config;
+use crate::fetch::{fetch_text, FetchError};
+use crate::BlogMeta;
+use serde::Deserialize;
+
+/// the content of comment file, either a list or under `comments`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CommentFile {
+    List(Vec<Comment>),
+    Object { comments: Vec<Comment> },
+}
+
+pub struct StaticFile {
+    dir: String,
+}
+
+impl StaticFile {
+    pub fn new(dir: &str) -> Self {
+        Self {
+            dir: dir.trim_matches('/').to_string(),
+        }
+    }
+
+    /// the path of comment file of the post
+    pub fn path(&self, meta: &BlogMeta) -> String {
+        let stem = meta
+            .path
+            .file_stem()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default();
+        format!("{}/{}.json", self.dir, stem)
+    }
+}
+
+impl CommentProvider for StaticFile {
+    fn load(&self, meta: &BlogMeta) -> CommentFuture {
+        let url = config::site_url(&self.path(meta));
+        Box::pin(async move {
+            log::debug!("{}", &format!("comments url: {:?}", url));
+            let text = match fetch_text(&url).await {
+                Ok(text) => text,
+                // no one commented yet
+                Err(FetchError::NotFound(_)) => return Ok(vec![]),
+                Err(e) => return Err(e),
+            };
+            match serde_json::from_str::<CommentFile>(&text) {
+                Ok(CommentFile::List(comments)) => Ok(comments),
+                Ok(CommentFile::Object { comments }) => Ok(comments),
+                Err(e) => Err(FetchError::Parse(format!("comments are not valid: {}", e))),
+            }
+        })
+    }
+}
@@ -0,0 +1,169 @@
+//! Here provide the events of analytics and their aggregation,
+//! the events carry the route and the reading only, no identity of visitors
+use serde::Deserialize;
+#[cfg(any(target_arch = "wasm32", test))]
+use serde::Serialize;
+#[cfg(any(target_arch = "wasm32", test))]
+use std::collections::{BTreeMap, HashMap};
+
+/// the sinks built in, see `AnalyticsConfig::sinks`
+pub const SINKS: &[&str] = &["console", "local", "beacon"];
+
+/// where the events go, `[analytics]` in `site.toml`
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AnalyticsConfig {
+    /// "console" logs them, "local" counts them in `localStorage`
+    /// and "beacon" posts them to `endpoint`
+    pub sinks: Vec<String>,
+    /// the url the beacon posts the events to
+    pub endpoint: String,
+    /// the percent of post scrolled to that it is read through
+    pub completed_depth: u8,
+}
+
+impl Default for AnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            sinks: vec!["local".into()],
+            endpoint: "".into(),
+            completed_depth: 90,
+        }
+    }
+}
+
+impl AnalyticsConfig {
+    /// the problems of config, the invalid sinks are removed
+    pub fn validate(&mut self) -> Vec<String> {
+        let mut errors = Vec::new();
+        for sink in &self.sinks {
+            if !SINKS.contains(&sink.as_str()) {
+                errors.push(format!(
+                    "analytics sink {:?} is unknown, expected one of {}",
+                    sink,
+                    SINKS.join(", ")
+                ));
+            }
+        }
+        self.sinks.retain(|e| SINKS.contains(&e.as_str()));
+        if self.sinks.iter().any(|e| e == "beacon") && !self.endpoint.starts_with("http") {
+            errors.push("analytics `endpoint` shall be an http(s) url for the beacon".into());
+            self.sinks.retain(|e| e != "beacon");
+        }
+        if self.completed_depth > 100 {
+            errors.push("analytics `completed_depth` is a percent, 100 is used".into());
+            self.completed_depth = 100;
+        }
+        errors
+    }
+}
+
+/// an event of the page routed to, `path` is the route, eg: "/posts/123/title"
+#[cfg(any(target_arch = "wasm32", test))]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    PageView {
+        path: String,
+    },
+    /// the post is left after `seconds`, scrolled to `depth` percent
+    Read {
+        path: String,
+        seconds: u64,
+        depth: u8,
+        completed: bool,
+    },
+}
+
+#[cfg(any(target_arch = "wasm32", test))]
+impl Event {
+    pub fn path(&self) -> &str {
+        match self {
+            Self::PageView { path } | Self::Read { path, .. } => path,
+        }
+    }
+}
+
+/// the counts of a path
+#[cfg(any(target_arch = "wasm32", test))]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    pub views: u64,
+    pub reads: u64,
+    pub completed: u64,
+    pub seconds: u64,
+}
+
+/// the counts of paths, they are kept on the device
+#[cfg(any(target_arch = "wasm32", test))]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Aggregate {
+    pub paths: BTreeMap<String, Stats>,
+}
+
+#[cfg(any(target_arch = "wasm32", test))]
+impl Aggregate {
+    pub fn record(&mut self, event: &Event) {
+        let stats = self.paths.entry(event.path().to_string()).or_default();
+        match event {
+            Event::PageView { .. } => stats.views += 1,
+            Event::Read {
+                seconds, completed, ..
+            } => {
+                stats.reads += 1;
+                stats.seconds += seconds;
+                if *completed {
+                    stats.completed += 1;
+                }
+            }
+        }
+    }
+
+    /// how hot the posts are by id, the views and the reads through,
+    /// the paths of a post under its aliases are added up
+    pub fn post_scores(&self) -> HashMap<u64, u64> {
+        let mut scores = HashMap::new();
+        for (path, stats) in &self.paths {
+            let mut items = path.trim_start_matches('/').split('/');
+            if items.next() != Some("posts") {
+                continue;
+            }
+            if let Some(Ok(id)) = items.next().map(|e| e.parse::<u64>()) {
+                *scores.entry(id).or_insert(0) += stats.views + stats.completed;
+            }
+        }
+        scores
+    }
+}
+
+#[test]
+fn test_analytics() {
+    let mut config = AnalyticsConfig {
+        sinks: vec!["local".into(), "beacon".into(), "google".into()],
+        completed_depth: 120,
+        ..Default::default()
+    };
+    assert_eq!(config.validate().len(), 3);
+    assert_eq!(config.sinks, vec!["local"]);
+    assert_eq!(config.completed_depth, 100);
+
+    let mut aggregate = Aggregate::default();
+    let view = Event::PageView {
+        path: "/posts/12/demo".into(),
+    };
+    aggregate.record(&view);
+    aggregate.record(&view);
+    aggregate.record(&Event::Read {
+        path: "/posts/12/old-demo".into(),
+        seconds: 30,
+        depth: 95,
+        completed: true,
+    });
+    aggregate.record(&Event::PageView { path: "/".into() });
+    assert_eq!(aggregate.paths["/posts/12/old-demo"].seconds, 30);
+    assert_eq!(aggregate.post_scores(), vec![(12, 3)].into_iter().collect());
+    assert_eq!(
+        serde_json::to_string(&view).unwrap(),
+        "{\"type\":\"page_view\",\"path\":\"/posts/12/demo\"}"
+    );
+}
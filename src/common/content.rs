@@ -0,0 +1,318 @@
+//! Here provide the blog and its meta parsed from the file
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+#[cfg(any(target_arch = "wasm32", test))]
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BlogMeta {
+    pub id: u64,
+    pub title: String,
+    pub timestamp: u64,
+    pub date: String,
+    pub path: PathBuf,
+    pub hero: String,
+}
+
+use std::hash::{Hash, Hasher};
+
+/// the hasher of ids, `usize` is hashed as `u32` as it is in wasm32,
+/// so the ids computed natively, eg: by the cli, are the same as the
+/// ones in browser
+struct IdHasher(DefaultHasher);
+
+impl Hasher for IdHasher {
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes)
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.0.write_u32(i as u32)
+    }
+}
+
+impl Hash for BlogMeta {
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: std::hash::Hasher,
+    {
+        self.title.hash(state);
+        self.timestamp.hash(state);
+        self.path.hash(state);
+    }
+}
+
+impl BlogMeta {
+    #[cfg(any(target_arch = "wasm32", test))]
+    pub fn new() -> Self {
+        Self {
+            id: 0,
+            path: PathBuf::new(),
+            timestamp: 0,
+            date: "".into(),
+            title: "".into(),
+            hero: "".into(),
+        }
+    }
+
+    pub fn get_hash(&mut self) {
+        let mut hasher = IdHasher(DefaultHasher::new());
+        self.hash(&mut hasher);
+        self.id = hasher.finish();
+    }
+
+    /// parse the meta from the path of file,
+    /// eg: "posts/2021-10-10-demo.rmd", `hero` is left empty
+    pub fn from_path(path: &str) -> Option<Self> {
+        let path = PathBuf::from(path);
+        if let Some(file_name) = path.file_name() {
+            if file_name.to_str().is_none() {
+                log::error!("file name is not valid: {:?}", path);
+                return None;
+            }
+            let file_name_str = file_name.to_str().unwrap();
+            let pat = regex::Regex::new(
+                r"(\d{2,4}\D\d{1,2}\D\d{1,2}(\D\d{1,2}){0,3})\D(?P<title>.*?)\.rmd$",
+            )
+            .unwrap();
+            //let path1 = "19-10-07-13-32-bolg-title-here";
+            //let path1 = "data/19-10-07-13-bolg-title-here.md";
+            //let path1 = "";
+            //self.path = path1.into();
+            let mut time_items = [0u64; 6];
+            const UNITS: [u64; 6] = [365 * 24 * 3600, 30 * 24 * 3600, 24 * 3600, 60 * 60, 60, 1];
+            // if path is matched
+            if let Some(cap) = pat.captures(file_name_str) {
+                let date = cap[1].into();
+                cap[1]
+                    .split(|c: char| !c.is_ascii_digit())
+                    .enumerate()
+                    .for_each(|(ind, e)| {
+                        let num = e.parse::<u64>().unwrap_or(0);
+                        time_items[ind] = num;
+                    });
+                if time_items[0] < 100 {
+                    time_items[0] += 32;
+                }
+                let sum = UNITS
+                    .iter()
+                    .zip(time_items.iter())
+                    .fold(0, |acc, (e1, e2)| acc + e1 * e2);
+                if cap.name("title").is_none() {
+                    log::error!(
+                        "title that consist of the file name is not valid: {:?}",
+                        path
+                    );
+                    return None;
+                }
+                let mut meta = Self {
+                    id: 0,
+                    title: cap.name("title").unwrap().as_str().into(),
+                    path,
+                    timestamp: sum,
+                    date,
+                    hero: "".into(),
+                };
+                meta.get_hash();
+
+                Some(meta)
+            } else {
+                log::error!("file name is not valid: {:?}", path);
+                None
+            }
+        } else {
+            log::error!("file name is not valid: {:?}", path);
+            None
+        }
+    }
+}
+
+/// make the name into lowercase words joined by `-`
+/// eg: "Bruce Yuan" -> "bruce-yuan"
+pub fn slugify(s: &str) -> String {
+    s.split(|c: char| !c.is_alphanumeric())
+        .filter(|e| !e.is_empty())
+        .map(|e| e.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// it represents a `Blog`
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Blog {
+    pub meta: BlogMeta,
+    pub tags: Vec<String>,
+    pub authors: Vec<String>,
+    /// language of the blog, empty for the site language
+    pub lang: String,
+    /// the blogs share the same key are translations of each other
+    pub translation: String,
+    /// the series the blog is part of, empty if it is not
+    #[serde(default)]
+    pub series: String,
+    /// the summary shown in search results and shares, the excerpt if empty
+    #[serde(default)]
+    pub description: String,
+    /// the image shown when the blog is shared, eg: "/assets/cover.png"
+    #[serde(default)]
+    pub cover: String,
+    /// the old paths of the blog, they are redirected to it
+    pub aliases: Vec<String>,
+    pub content: Vec<String>,
+    pub published: bool,
+    pub ignored: bool,
+}
+
+impl Blog {
+    /// authors of the blog, `default` if not specified
+    pub fn authors_or(&self, default: &str) -> Vec<String> {
+        if self.authors.is_empty() {
+            vec![default.to_string()]
+        } else {
+            self.authors.clone()
+        }
+    }
+
+    /// language of the blog, `default` if not specified
+    pub fn lang_or(&self, default: &str) -> String {
+        if self.lang.is_empty() {
+            default.to_string()
+        } else {
+            self.lang.clone()
+        }
+    }
+
+    /// extract date infomation from blog
+    pub fn date_info(&mut self, date: Option<&str>) {
+        // try get it from path
+        // eg: 2019-10-07-bolg-title-here;  19-3-7-bolg-title-here
+        // eg: 2019-10-07-13-32-bolg-title-here;  19-3-7-01-59-bolg-title-here
+        // note that the format is descending: yyyy-mm-dd-hh-MM-ss
+        // and the accuracy is second and
+        // year,month,day are required
+        let pat = regex::Regex::new(r"(\d{2,4}\D\d{1,2}\D\d{1,2}(\D\d{1,2}){0,3})").unwrap();
+        //let path1 = "19-10-07-13-32-bolg-title-here";
+        //let path1 = "data/19-10-07-13-bolg-title-here.md";
+        //let path1 = "";
+        //self.path = path1.into();
+        let mut time_items = [0u64; 6];
+        const UNITS: [u64; 6] = [365 * 24 * 3600, 30 * 24 * 3600, 24 * 3600, 60 * 60, 60, 1];
+        // if path is matched
+        if let Some(cap) = pat.captures(self.meta.path.to_str().unwrap()) {
+            cap[1]
+                .split(|c: char| !c.is_ascii_digit())
+                .enumerate()
+                .for_each(|(ind, e)| {
+                    let num = e.parse::<u64>().unwrap_or(0);
+                    time_items[ind] = num;
+                });
+            if time_items[0] < 100 {
+                time_items[0] += 32;
+            }
+            let sum = UNITS
+                .iter()
+                .zip(time_items.iter())
+                .fold(0, |acc, (e1, e2)| acc + e1 * e2);
+            self.meta.timestamp = sum;
+        } else {
+            // path is not matched but pre-defined
+            // try get it from meta data in pre-defined info
+            // eg: date: 2019-10-07
+            match date {
+                Some(s) => {
+                    if let Some(cap) = pat.captures(s) {
+                        cap[1]
+                            .split(|c: char| !c.is_ascii_digit())
+                            .enumerate()
+                            .for_each(|(ind, e)| {
+                                let num = e.parse::<u64>().unwrap_or(0);
+                                time_items[ind] = num;
+                            });
+                        if time_items[0] < 100 {
+                            time_items[0] += 2000;
+                        }
+                        let sum = UNITS
+                            .iter()
+                            .zip(time_items.iter())
+                            .fold(0, |acc, (e1, e2)| acc + e1 * e2);
+                        self.meta.timestamp = sum;
+                    }
+                }
+                // not know
+                None => {
+                    log::error!("Time Stampe is not found in file name nor defined in file");
+                    log::error!("file is ignored to proceed: {:?}", self.meta.path);
+                    self.ignored = true;
+                }
+            }
+        }
+    }
+}
+
+/// the part of a post in `posts/manifest.json` that the site reads
+#[cfg(any(target_arch = "wasm32", test))]
+#[derive(Deserialize)]
+struct ManifestItem {
+    id: u64,
+    #[serde(default)]
+    authors: Vec<String>,
+}
+
+/// the authors of posts by id in the manifest generated by `noer build`,
+/// so the bylines are known without fetching the posts
+#[cfg(any(target_arch = "wasm32", test))]
+pub fn manifest_authors(json: &str) -> Result<HashMap<u64, Vec<String>>, String> {
+    let items: Vec<ManifestItem> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    Ok(items.into_iter().map(|e| (e.id, e.authors)).collect())
+}
+
+#[test]
+fn test_manifest_authors() {
+    let authors = manifest_authors(
+        r#"[{"id": 1, "title": "a", "authors": ["Alice", "Bob"]}, {"id": 2, "authors": []}]"#,
+    )
+    .unwrap();
+    assert_eq!(authors[&1], vec!["Alice", "Bob"]);
+    assert!(authors[&2].is_empty());
+    assert!(manifest_authors("<html>").is_err());
+}
+
+#[test]
+fn test_date_info() {
+    let mut blog = Blog {
+        meta: BlogMeta::new(),
+        tags: vec![],
+        authors: vec![],
+        lang: "".into(),
+        translation: "".into(),
+        series: "".into(),
+        description: "".into(),
+        cover: "".into(),
+        aliases: vec![],
+        content: vec![],
+        published: false,
+        ignored: false,
+    };
+    const DAY: u64 = 24 * 3600;
+    blog.date_info(Some("2019-10-07"));
+    let day = blog.meta.timestamp;
+    assert!(day > 0);
+    blog.date_info(Some("2019-10-07-02-01"));
+    assert_eq!(blog.meta.timestamp, day + 2 * 3600 + 60);
+    blog.date_info(Some("2019/10/07/02/01"));
+    assert_eq!(blog.meta.timestamp, day + 2 * 3600 + 60);
+    blog.date_info(Some("2019/10/07 19:57"));
+    assert_eq!(blog.meta.timestamp, day + 19 * 3600 + 57 * 60);
+    blog.date_info(Some("2019-10/07 19:57:36"));
+    assert_eq!(blog.meta.timestamp, day + 19 * 3600 + 57 * 60 + 36);
+    blog.date_info(Some("19-10-08"));
+    assert_eq!(blog.meta.timestamp, day + DAY);
+    assert!(!blog.ignored);
+    blog.date_info(None);
+    assert!(blog.ignored);
+}
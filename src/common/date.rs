@@ -0,0 +1,27 @@
+//! Here provide the parsing of the dates of posts, eg: "2021-10-10",
+//! "21-10-10-08-05" or "2021-10-10 08:05:00 +0800"
+
+/// [year, month, day, hour, minute, second] of date, they are the runs of
+/// digits in order and the missing ones are 0, the two-digit years are in
+/// the 2000s, eg: "21-10-10 08:05" is [2021, 10, 10, 8, 5, 0]
+pub fn parse_date(date: &str) -> [u64; 6] {
+    let mut items = [0u64; 6];
+    date.split(|c: char| !c.is_ascii_digit())
+        .filter(|e| !e.is_empty())
+        .zip(items.iter_mut())
+        .for_each(|(e, item)| *item = e.parse().unwrap_or(0));
+    if items[0] < 100 {
+        items[0] += 2000;
+    }
+    items
+}
+
+#[test]
+fn test_parse_date() {
+    assert_eq!(parse_date("2021-10-10"), [2021, 10, 10, 0, 0, 0]);
+    assert_eq!(parse_date("21-10-10 08:05"), [2021, 10, 10, 8, 5, 0]);
+    assert_eq!(
+        parse_date("2021-10-10T13:32:05+08:00"),
+        [2021, 10, 10, 13, 32, 5]
+    );
+}
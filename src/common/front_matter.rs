@@ -0,0 +1,170 @@
+//! Here provide the parsing of front matter
+//! eg:
+//! ---
+//! title: demo
+//! published: true
+//! tags:
+//!  - markdown
+//! ---
+use super::content::{Blog, BlogMeta};
+use std::collections::HashMap;
+
+/// the keys known in front matter
+pub const KEYS: &[&str] = &[
+    "title",
+    "published",
+    "date",
+    "tags",
+    "tag",
+    "authors",
+    "author",
+    "aliases",
+    "alias",
+    "lang",
+    "translation",
+    "series",
+    "description",
+    "cover",
+];
+
+/// parse the content of file into blog, the front matter between `---`
+/// is made into the attributes and the rest is the content,
+/// None if the front matter is missing or invalid
+pub fn str2blog(s: &str, meta: &BlogMeta) -> Option<Blog> {
+    log::trace!("parsing a string into a blog");
+    parse_blog(s, meta)
+        .map_err(|e| log::error!("{} for {:?}", e, meta.path))
+        .ok()
+}
+
+/// the keys in front matter, in the order they appear
+pub fn keys(s: &str) -> Vec<String> {
+    let pat = regex::Regex::new(r"^(?P<key>\w+)\s*:").unwrap();
+    s.split("---")
+        .nth(1)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| pat.captures(line))
+        .map(|caps| caps["key"].to_string())
+        .collect()
+}
+
+/// same as `str2blog`, the reason is returned if it fails
+pub fn parse_blog(s: &str, meta: &BlogMeta) -> Result<Blog, String> {
+    let sp = s.splitn(3, "---").collect::<Vec<_>>();
+    if sp.len() < 3 {
+        return Err("Front Matter Missing".into());
+    }
+    let metadata = sp[1].trim();
+    let cont = sp[2]
+        .splitn(2, "<!--break-->")
+        .map(|e| e.trim().to_owned())
+        .collect::<Vec<String>>();
+    let pat = regex::Regex::new(r"(?P<key>\w+)\s*:\s*(?P<value>.*?$)").unwrap();
+    let pat_tag = regex::Regex::new(r"\s*-\s+(?P<tag>.*?$)").unwrap();
+    let pat_ml = regex::Regex::new(r"(\s{2,}||\t{1,})(?P<ln>.*?$)").unwrap();
+    let mut map = HashMap::new();
+    // items of list under the key
+    let mut lists: HashMap<&str, Vec<String>> = HashMap::new();
+    let mut lastkey = "";
+    for line in metadata.split("\n") {
+        if line.trim().is_empty() {
+            // empty line skip it
+            lastkey = "";
+            continue;
+        }
+        if let Some(caps) = pat.captures(line) {
+            if caps.len() == 1 {
+                log::trace!("Not Matched: {}", line);
+                break;
+            }
+            let key = caps.name("key").map_or("", |e| e.as_str());
+            lastkey = key;
+            let value = caps.name("value").map_or("", |e| e.as_str());
+            map.insert(key, value.trim().to_owned());
+            continue;
+        }
+        if let Some(caps) = pat_tag.captures(line) {
+            if let Some(tag) = caps.name("tag") {
+                let tag = tag.as_str().trim().to_owned();
+                lists.entry(lastkey).or_default().push(tag);
+                continue;
+            }
+        }
+        if let Some(caps) = pat_ml.captures(line) {
+            let ln = caps.name("ln").unwrap().as_str().trim();
+            if let Some(item) = map.get_mut(&lastkey) {
+                item.push_str(ln);
+            } else {
+                log::debug!("Line ingnored: {}", ln);
+            }
+        }
+    }
+    if !map.contains_key("title") {
+        return Err("Title Missing".into());
+    }
+    let published = match map.remove("published").map(|e| e.parse::<bool>()) {
+        Some(Ok(published)) => published,
+        Some(Err(_)) => {
+            return Err("Attribute `published` Not A Bool".into());
+        }
+        None => {
+            return Err("Attribute `published` Missing".into());
+        }
+    };
+    let mut tags = take_list(&map, &mut lists, "tags");
+    tags.extend(take_list(&map, &mut lists, "tag"));
+    let mut authors = take_list(&map, &mut lists, "authors");
+    authors.extend(take_list(&map, &mut lists, "author"));
+    let mut aliases = take_list(&map, &mut lists, "aliases");
+    aliases.extend(take_list(&map, &mut lists, "alias"));
+    let mut blog = Blog {
+        meta: meta.clone(),
+        tags,
+        authors,
+        lang: map.remove("lang").unwrap_or_default(),
+        translation: map.remove("translation").unwrap_or_default(),
+        series: map.remove("series").unwrap_or_default(),
+        description: map.remove("description").unwrap_or_default(),
+        cover: map.remove("cover").unwrap_or_default(),
+        aliases,
+        content: cont,
+        published,
+        ignored: false,
+    };
+    match map.remove("date") {
+        Some(s) => blog.date_info(Some(&s)),
+        None => blog.date_info(None),
+    }
+    blog.meta.get_hash();
+    Ok(blog)
+}
+
+/// take the list of key in front matter, it is either
+/// items following the key or inline values separated by comma
+/// eg:
+/// ```text
+/// authors:
+///  - Alice
+///  - Bob
+/// ```
+/// or `authors: Alice, Bob`
+fn take_list(
+    map: &HashMap<&str, String>,
+    lists: &mut HashMap<&str, Vec<String>>,
+    key: &str,
+) -> Vec<String> {
+    if let Some(items) = lists.remove(key) {
+        return items;
+    }
+    map.get(key)
+        .map(|value| {
+            value
+                .trim_matches(|c| c == '[' || c == ']')
+                .split(',')
+                .map(|e| e.trim().trim_matches('"').to_owned())
+                .filter(|e| !e.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,92 @@
+//! Here provide the fuzzy matching of text
+//! eg: the path not found against the titles of posts,
+//! each word of query is matched with the most similar word of text,
+//! so typos and missing words are tolerated
+
+/// the score below which the text is not considered a match
+pub const THRESHOLD: f64 = 0.5;
+
+/// the words of text in lowercase, numbers are dropped
+/// eg: "posts/123/A-Free_translator" -> ["posts", "a", "free", "translator"]
+pub fn words(s: &str) -> Vec<String> {
+    s.split(|c: char| !c.is_alphanumeric())
+        .filter(|e| !e.is_empty() && !e.chars().all(|c| c.is_ascii_digit()))
+        .map(|e| e.to_lowercase())
+        .collect()
+}
+
+/// the edit distance of two strings
+fn distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(row[j]).min(cur)
+            };
+            prev = cur;
+        }
+    }
+    row[b.len()]
+}
+
+/// the similarity of two strings in [0, 1]
+fn similarity(a: &str, b: &str) -> f64 {
+    let len = a.chars().count().max(b.chars().count());
+    if len == 0 {
+        return 1.0;
+    }
+    1.0 - distance(a, b) as f64 / len as f64
+}
+
+/// how well the query matches the text in [0, 1]
+pub fn score(query: &str, text: &str) -> f64 {
+    let query = words(query);
+    let text = words(text);
+    if query.is_empty() || text.is_empty() {
+        return 0.0;
+    }
+    let coverage = query
+        .iter()
+        .map(|w| text.iter().map(|e| similarity(w, e)).fold(0.0, f64::max))
+        .sum::<f64>()
+        / query.len() as f64;
+    // the whole text counts a little, so the extra words lower the score
+    let whole = similarity(&query.join("-"), &text.join("-"));
+    (coverage * 3.0 + whole) / 4.0
+}
+
+/// at most `n` items that match the query best, the text of item is
+/// matched, the ones score below `THRESHOLD` are dropped
+pub fn best<T, I>(query: &str, items: I, n: usize) -> Vec<T>
+where
+    I: IntoIterator<Item = (T, String)>,
+{
+    let mut scored = items
+        .into_iter()
+        .map(|(item, text)| (score(query, &text), item))
+        .filter(|(score, _)| *score >= THRESHOLD)
+        .collect::<Vec<_>>();
+    scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+    scored.into_iter().take(n).map(|(_, item)| item).collect()
+}
+
+#[test]
+fn test_best() {
+    let titles = vec![
+        (1, "A-free-and-unlimited-translator".to_string()),
+        (2, "high-order-pointer-in-c-rust".to_string()),
+        (3, "file-manipulation-in-web-assembly".to_string()),
+    ];
+    assert_eq!(
+        best("posts/123/free-translater", titles.clone(), 3),
+        vec![1]
+    );
+    assert_eq!(best("pointer rust", titles.clone(), 3), vec![2]);
+    assert!(best("nothing-like-this", titles, 3).is_empty());
+}
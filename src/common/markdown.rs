@@ -0,0 +1,295 @@
+//! Here provide the rendering of markdown into html string
+use super::content::slugify;
+use pulldown_cmark::escape::{escape_href, escape_html};
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Options, Tag};
+
+/// the markdown extensions enabled
+pub fn options() -> Options {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_HEADING_ATTRIBUTES);
+    options.insert(Options::ENABLE_SMART_PUNCTUATION);
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+    options
+}
+
+/// the ids of headings in order, the ones given by `{#id}` are kept
+/// and the others are made from the text, eg: "Getting Started" -> "getting-started",
+/// the same ones are numbered, eg: "usage-1"
+fn heading_ids(events: &[Event]) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::new();
+    let mut heading: Option<String> = None;
+    for event in events {
+        match event {
+            Event::Start(Tag::Heading(_, Some(id), _)) => ids.push(id.to_string()),
+            Event::Start(Tag::Heading(_, None, _)) => heading = Some(String::new()),
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(heading) = heading.as_mut() {
+                    heading.push_str(text);
+                }
+            }
+            Event::End(Tag::Heading(_, None, _)) => {
+                let slug = match slugify(&heading.take().unwrap_or_default()) {
+                    slug if slug.is_empty() => "section".to_string(),
+                    slug => slug,
+                };
+                let mut id = slug.clone();
+                let mut n = 0;
+                while ids.contains(&id) {
+                    n += 1;
+                    id = format!("{}-{}", slug, n);
+                }
+                ids.push(id);
+            }
+            _ => {}
+        }
+    }
+    ids
+}
+
+/// the attributes of code block in the info string of its fence,
+/// eg: "rust,title=src/main.rs,linenos,hl_lines=3-5"
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CodeInfo {
+    pub lang: String,
+    /// the caption of code, eg: the file name
+    pub title: String,
+    pub linenos: bool,
+    /// the ranges of lines highlighted, from 1 and inclusive
+    pub highlighted: Vec<(usize, usize)>,
+}
+
+impl CodeInfo {
+    pub fn parse(info: &str) -> Self {
+        let mut code = Self::default();
+        for (i, item) in info.split(',').map(|e| e.trim()).enumerate() {
+            match item.split_once('=') {
+                Some((key, value)) => match key.trim() {
+                    "title" => code.title = value.trim().trim_matches('"').to_string(),
+                    // eg: "3-5", "1 3-5" or "1|3-5"
+                    "hl_lines" => {
+                        code.highlighted = value
+                            .split(|c: char| c.is_whitespace() || c == '|')
+                            .filter_map(|e| {
+                                let (start, end) = e.split_once('-').unwrap_or((e, e));
+                                Some((start.trim().parse().ok()?, end.trim().parse().ok()?))
+                            })
+                            .collect()
+                    }
+                    key => log::debug!("code block attribute {:?} ignored", key),
+                },
+                None if item == "linenos" => code.linenos = true,
+                None if i == 0 => code.lang = item.to_string(),
+                None => log::debug!("code block attribute {:?} ignored", item),
+            }
+        }
+        code
+    }
+
+    fn is_highlighted(&self, line: usize) -> bool {
+        self.highlighted
+            .iter()
+            .any(|(start, end)| *start <= line && line <= *end)
+    }
+}
+
+/// the html of code block, a figure with the caption and the lines,
+/// the line numbers are counted by css, so they are not copied along
+fn code_block(info: &CodeInfo, code: &str) -> String {
+    let mut html = String::with_capacity(code.len() * 3 / 2);
+    html.push_str("<figure class=\"code-block");
+    if info.linenos {
+        html.push_str(" has-line-numbers");
+    }
+    html.push_str("\">");
+    if !info.title.is_empty() {
+        html.push_str("<figcaption class=\"code-title\">");
+        let _ = escape_html(&mut html, &info.title);
+        html.push_str("</figcaption>");
+    }
+    html.push_str("<pre><code");
+    if !info.lang.is_empty() {
+        html.push_str(" class=\"language-");
+        let _ = escape_html(&mut html, &info.lang);
+        html.push('"');
+    }
+    html.push('>');
+    if info.linenos || !info.highlighted.is_empty() {
+        for (i, line) in code.lines().enumerate() {
+            match info.is_highlighted(i + 1) {
+                true => html.push_str("<span class=\"line is-highlighted\">"),
+                false => html.push_str("<span class=\"line\">"),
+            }
+            let _ = escape_html(&mut html, line);
+            html.push_str("</span>\n");
+        }
+    } else {
+        let _ = escape_html(&mut html, code);
+    }
+    html.push_str("</code></pre></figure>\n");
+    html
+}
+
+/// an image of post, the size is given in the fragment of url where it is
+/// known, eg: `![diagram](/assets/a.png#640x480 "the memory layout")`
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Image {
+    src: String,
+    alt: String,
+    title: String,
+    size: Option<(u32, u32)>,
+}
+
+impl Image {
+    fn new(url: &str, title: &str) -> Self {
+        let size = url.rsplit_once('#').and_then(|(src, fragment)| {
+            let (width, height) = fragment.split_once('x')?;
+            Some((src, (width.parse().ok()?, height.parse().ok()?)))
+        });
+        let (src, size) = match size {
+            Some((src, size)) => (src, Some(size)),
+            None => (url, None),
+        };
+        Self {
+            src: src.to_string(),
+            alt: String::new(),
+            title: title.to_string(),
+            size,
+        }
+    }
+
+    /// the `<img>`, loaded once it is about to be scrolled into view
+    fn img(&self, with_title: bool) -> String {
+        let mut html = String::from("<img src=\"");
+        let _ = escape_href(&mut html, &self.src);
+        html.push_str("\" alt=\"");
+        let _ = escape_html(&mut html, &self.alt);
+        html.push('"');
+        if with_title && !self.title.is_empty() {
+            html.push_str(" title=\"");
+            let _ = escape_html(&mut html, &self.title);
+            html.push('"');
+        }
+        if let Some((width, height)) = self.size {
+            html.push_str(&format!(" width=\"{}\" height=\"{}\"", width, height));
+        }
+        html.push_str(" loading=\"lazy\" />");
+        html
+    }
+
+    /// the figure of image, the title is its caption
+    fn figure(&self) -> String {
+        let mut html = String::from("<figure class=\"image-figure\">");
+        html.push_str(&self.img(false));
+        html.push_str("<figcaption>");
+        let _ = escape_html(&mut html, &self.title);
+        html.push_str("</figcaption></figure>\n");
+        html
+    }
+}
+
+/// render markdown into html string, the headings are given ids
+/// so that they are linked to, eg: `/posts/123/title#getting-started`,
+/// the code blocks are rendered with their attributes, see `CodeInfo`,
+/// and the images with a title alone in their paragraphs are figures
+pub fn to_html(md: &str) -> String {
+    let events = pulldown_cmark::Parser::new_ext(md, options()).collect::<Vec<_>>();
+    let ids = heading_ids(&events);
+    let mut ids = ids.iter();
+    // the code block being read, its attributes and text
+    let mut code: Option<(CodeInfo, String)> = None;
+    // the image whose alt text is being read
+    let mut image: Option<Image> = None;
+    let mut output_events = Vec::with_capacity(events.len());
+    let mut events = events.into_iter().peekable();
+    while let Some(event) = events.next() {
+        match event {
+            Event::Start(Tag::Heading(level, _, classes)) => output_events.push(Event::Start(
+                Tag::Heading(level, ids.next().map(|e| e.as_str()), classes),
+            )),
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let info = match kind {
+                    CodeBlockKind::Fenced(info) => CodeInfo::parse(&info),
+                    CodeBlockKind::Indented => CodeInfo::default(),
+                };
+                code = Some((info, String::new()));
+            }
+            Event::Text(text) if code.is_some() => {
+                if let Some((_, s)) = code.as_mut() {
+                    s.push_str(&text);
+                }
+            }
+            Event::Start(Tag::Image(_, url, title)) => image = Some(Image::new(&url, &title)),
+            Event::Text(text) | Event::Code(text) if image.is_some() => {
+                if let Some(image) = image.as_mut() {
+                    image.alt.push_str(&text);
+                }
+            }
+            Event::End(Tag::Image(..)) => {
+                let image = match image.take() {
+                    Some(image) => image,
+                    None => continue,
+                };
+                let alone = matches!(output_events.last(), Some(Event::Start(Tag::Paragraph)))
+                    && matches!(events.peek(), Some(Event::End(Tag::Paragraph)));
+                if alone && !image.title.is_empty() {
+                    output_events.pop();
+                    events.next();
+                    output_events.push(Event::Html(CowStr::from(image.figure())));
+                } else {
+                    output_events.push(Event::Html(CowStr::from(image.img(true))));
+                }
+            }
+            // the markup in alt text is dropped
+            _ if image.is_some() => {}
+            Event::End(Tag::CodeBlock(_)) => {
+                if let Some((info, text)) = code.take() {
+                    output_events.push(Event::Html(CowStr::from(code_block(&info, &text))));
+                }
+            }
+            event => output_events.push(event),
+        }
+    }
+    let mut output = String::with_capacity(md.len() * 3 / 2);
+    pulldown_cmark::html::push_html(&mut output, output_events.into_iter());
+    output
+}
+
+#[test]
+fn test_code_block() {
+    let info = CodeInfo::parse("rust, title=\"src/main.rs\" ,linenos,hl_lines=1 3-4");
+    assert_eq!(info.lang, "rust");
+    assert_eq!(info.title, "src/main.rs");
+    assert!(info.linenos);
+    assert_eq!(info.highlighted, vec![(1, 1), (3, 4)]);
+    assert_eq!(
+        to_html("```rust,title=a<b>.rs,hl_lines=2\nfn a() {}\nlet b = 1 < 2;\n```"),
+        "<figure class=\"code-block\"><figcaption class=\"code-title\">a&lt;b&gt;.rs</figcaption><pre><code class=\"language-rust\"><span class=\"line\">fn a() {}</span>\n<span class=\"line is-highlighted\">let b = 1 &lt; 2;</span>\n</code></pre></figure>\n"
+    );
+    assert_eq!(
+        to_html("    indented\n"),
+        "<figure class=\"code-block\"><pre><code>indented\n</code></pre></figure>\n"
+    );
+}
+
+#[test]
+fn test_image() {
+    assert_eq!(
+        to_html("![a *b*](/assets/a.png#640x480 \"the \\\"layout\\\"\")"),
+        "<figure class=\"image-figure\"><img src=\"/assets/a.png\" alt=\"a b\" width=\"640\" height=\"480\" loading=\"lazy\" /><figcaption>the &quot;layout&quot;</figcaption></figure>\n"
+    );
+    assert_eq!(
+        to_html("see ![a](a.png#top \"t\") and ![b](b.png)"),
+        "<p>see <img src=\"a.png#top\" alt=\"a\" title=\"t\" loading=\"lazy\" /> and <img src=\"b.png\" alt=\"b\" loading=\"lazy\" /></p>\n"
+    );
+}
+
+#[test]
+fn test_heading_ids() {
+    assert_eq!(
+        to_html("# Getting Started\n## Usage\n## Usage\n## `noer` {#cli}\n## 安装"),
+        "<h1 id=\"getting-started\">Getting Started</h1>\n<h2 id=\"usage\">Usage</h2>\n<h2 id=\"usage-1\">Usage</h2>\n<h2 id=\"cli\"><code>noer</code></h2>\n<h2 id=\"安装\">安装</h2>\n"
+    );
+}
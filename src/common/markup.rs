@@ -0,0 +1,319 @@
+//! Here provide the reading of XML and HTML into elements, the XML is
+//! read strictly while the HTML is read as browsers do, leniently,
+//! and the writing of them back, eg: the HTML of posts into XHTML.
+//! the HTML of posts is sanitized by it in browser, and the cli reads
+//! the exports of other blogs by it
+use std::fmt::Write;
+
+/// the elements of HTML that are never closed
+const VOID: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    Element(Element),
+    Text(String),
+    Comment(String),
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Element {
+    pub name: String,
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<Node>,
+}
+
+impl Element {
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// the child elements named
+    pub fn all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children.iter().filter_map(move |e| match e {
+            Node::Element(e) if e.name == name => Some(e),
+            _ => None,
+        })
+    }
+
+    pub fn first(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find_map(|e| match e {
+            Node::Element(e) if e.name == name => Some(e),
+            _ => None,
+        })
+    }
+
+    /// the text of the child element named, empty if it is missing
+    pub fn child_text(&self, name: &str) -> String {
+        self.first(name).map(Element::text).unwrap_or_default()
+    }
+
+    /// all the text in it
+    pub fn text(&self) -> String {
+        let mut s = String::new();
+        for child in &self.children {
+            match child {
+                Node::Element(e) => s.push_str(&e.text()),
+                Node::Text(text) => s.push_str(text),
+                Node::Comment(_) => {}
+            }
+        }
+        s
+    }
+
+    /// it written back into HTML
+    pub fn html(&self) -> String {
+        let mut s = String::new();
+        self.write(&mut s, false);
+        s
+    }
+
+    fn write(&self, s: &mut String, xhtml: bool) {
+        let _ = write!(s, "<{}", self.name);
+        for (k, v) in &self.attrs {
+            let _ = write!(s, " {}=\"{}\"", k, escape(v));
+        }
+        if VOID.contains(&self.name.as_str()) {
+            s.push_str(if xhtml { "/>" } else { ">" });
+            return;
+        }
+        s.push('>');
+        self.write_children(s, xhtml);
+        let _ = write!(s, "</{}>", self.name);
+    }
+
+    /// the children written back, eg: the content of document
+    pub fn write_children(&self, s: &mut String, xhtml: bool) {
+        for child in &self.children {
+            match child {
+                Node::Element(e) => e.write(s, xhtml),
+                // the text is decoded once parsed, so it is escaped entirely
+                Node::Text(text) => s.push_str(&escape(text)),
+                Node::Comment(_) if xhtml => {}
+                Node::Comment(text) => {
+                    let _ = write!(s, "<!--{}-->", text);
+                }
+            }
+        }
+    }
+}
+
+/// the text escaped for HTML or XML, in the text or the quoted attributes
+pub fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// the text with the entities decoded, the unknown ones are kept
+pub fn decode(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+    let pat = regex::Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap();
+    pat.replace_all(s, |caps: &regex::Captures| {
+        let name = &caps[1];
+        let c = match name {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            "hellip" => Some('…'),
+            "ndash" => Some('–'),
+            "mdash" => Some('—'),
+            "lsquo" => Some('‘'),
+            "rsquo" => Some('’'),
+            "ldquo" => Some('“'),
+            "rdquo" => Some('”'),
+            "copy" => Some('©'),
+            _ => match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                None => name
+                    .strip_prefix('#')
+                    .and_then(|e| e.parse().ok())
+                    .and_then(char::from_u32),
+            },
+        };
+        c.map_or_else(|| caps[0].to_string(), String::from)
+    })
+    .to_string()
+}
+
+enum Token {
+    /// the name, attributes and whether it is closed by `/>`
+    Open(String, Vec<(String, String)>, bool),
+    Close(String),
+    Text(String),
+    Comment(String),
+}
+
+/// the tokens of markup, the declarations and instructions are skipped
+fn tokens(s: &str) -> Result<Vec<Token>, String> {
+    let tag = regex::Regex::new(
+        r"^<(/?)([A-Za-z][\w:.-]*)((?:[^>\x22']|\x22[^\x22]*\x22|'[^']*')*?)(/?)>",
+    )
+    .unwrap();
+    let attr = regex::Regex::new(r#"([^\s=/"']+)(?:\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+)))?"#)
+        .unwrap();
+    let mut tokens = Vec::new();
+    let mut rest = s;
+    while !rest.is_empty() {
+        let (skip, token) = if let Some(after) = rest.strip_prefix("<!--") {
+            let end = after.find("-->").ok_or("a comment is not closed")?;
+            (4 + end + 3, Some(Token::Comment(after[..end].to_string())))
+        } else if let Some(after) = rest.strip_prefix("<![CDATA[") {
+            let end = after.find("]]>").ok_or("a CDATA is not closed")?;
+            (9 + end + 3, Some(Token::Text(after[..end].to_string())))
+        } else if rest.starts_with("<?") || rest.starts_with("<!") {
+            let end = rest.find('>').ok_or("a declaration is not closed")?;
+            (end + 1, None)
+        } else if let Some(caps) = tag.captures(rest) {
+            let name = caps[2].to_string();
+            let token = if &caps[1] == "/" {
+                Token::Close(name)
+            } else {
+                let attrs = attr
+                    .captures_iter(&caps[3])
+                    .map(|e| {
+                        let value = (2..=4).find_map(|i| e.get(i)).map_or("", |e| e.as_str());
+                        (e[1].to_string(), decode(value))
+                    })
+                    .collect();
+                Token::Open(name, attrs, &caps[4] == "/")
+            };
+            (caps[0].len(), Some(token))
+        } else {
+            // a `<` that starts no tag is a text
+            let end = match rest.strip_prefix('<') {
+                Some(after) => after.find('<').map_or(rest.len(), |e| e + 1),
+                None => rest.find('<').unwrap_or(rest.len()),
+            };
+            (end, Some(Token::Text(decode(&rest[..end]))))
+        };
+        tokens.extend(token);
+        rest = &rest[skip..];
+    }
+    Ok(tokens)
+}
+
+/// the element holding the nodes, it is the last of stack
+fn push(stack: &mut [Element], node: Node) {
+    if let Some(parent) = stack.last_mut() {
+        match (parent.children.last_mut(), node) {
+            (Some(Node::Text(text)), Node::Text(more)) => text.push_str(&more),
+            (_, node) => parent.children.push(node),
+        }
+    }
+}
+
+/// the stack is closed down to the element, the elements in it are closed
+fn close(stack: &mut Vec<Element>, depth: usize) {
+    while stack.len() > depth {
+        let element = stack.pop().unwrap();
+        push(stack, Node::Element(element));
+    }
+}
+
+/// the XML document, its root element is the only child of the returned one
+pub fn parse_xml(s: &str) -> Result<Element, String> {
+    let mut stack = vec![Element::default()];
+    for token in tokens(s)? {
+        match token {
+            Token::Open(name, attrs, true) => push(
+                &mut stack,
+                Node::Element(Element {
+                    name,
+                    attrs,
+                    children: vec![],
+                }),
+            ),
+            Token::Open(name, attrs, false) => stack.push(Element {
+                name,
+                attrs,
+                children: vec![],
+            }),
+            Token::Close(name) if stack.len() > 1 && stack.last().unwrap().name == name => {
+                let depth = stack.len() - 1;
+                close(&mut stack, depth)
+            }
+            Token::Close(name) => return Err(format!("</{}> closes no element", name)),
+            Token::Text(text) => push(&mut stack, Node::Text(text)),
+            Token::Comment(text) => push(&mut stack, Node::Comment(text)),
+        }
+    }
+    if let Some(element) = stack.get(1) {
+        return Err(format!("<{}> is not closed", element.name));
+    }
+    Ok(stack.pop().unwrap())
+}
+
+/// the HTML fragment, the elements not closed are closed by their parents
+/// and the closing tags not opened are ignored
+pub fn parse_html(s: &str) -> Element {
+    let mut stack = vec![Element::default()];
+    // the HTML is not well-formed, the unclosed comments are texts
+    let tokens = tokens(s).unwrap_or_else(|_| vec![Token::Text(decode(s))]);
+    for token in tokens {
+        match token {
+            Token::Open(name, attrs, closed) => {
+                let name = name.to_lowercase();
+                let element = Element {
+                    name,
+                    attrs,
+                    children: vec![],
+                };
+                if closed || VOID.contains(&element.name.as_str()) {
+                    push(&mut stack, Node::Element(element));
+                } else {
+                    stack.push(element);
+                }
+            }
+            Token::Close(name) => {
+                let name = name.to_lowercase();
+                if let Some(depth) = stack.iter().skip(1).rposition(|e| e.name == name) {
+                    close(&mut stack, depth + 1);
+                }
+            }
+            Token::Text(text) => push(&mut stack, Node::Text(text)),
+            Token::Comment(text) => push(&mut stack, Node::Comment(text)),
+        }
+    }
+    close(&mut stack, 1);
+    stack.pop().unwrap()
+}
+
+#[test]
+fn test_xml() {
+    let root = parse_xml(
+        "<?xml version=\"1.0\"?>\n<rss><item a='1'><title>A &amp; B</title><content:encoded><![CDATA[<p>x</p>]]></content:encoded><empty/></item></rss>",
+    )
+    .unwrap();
+    let item = root.first("rss").unwrap().first("item").unwrap();
+    assert_eq!(item.attr("a"), Some("1"));
+    assert_eq!(item.child_text("title"), "A & B");
+    assert_eq!(item.child_text("content:encoded"), "<p>x</p>");
+    assert!(item.first("empty").is_some());
+    assert!(parse_xml("<a><b></a>").is_err());
+
+    let html = parse_html("<p>a<br>b<img src=\"x.png\"></p><p>c</i> 1 < 2</p><ul><li>d");
+    assert_eq!(
+        html.html(),
+        "<><p>a<br>b<img src=\"x.png\"></p><p>c 1 &lt; 2</p><ul><li>d</li></ul></>"
+    );
+    let mut xhtml = String::new();
+    html.write_children(&mut xhtml, true);
+    assert_eq!(
+        xhtml,
+        "<p>a<br/>b<img src=\"x.png\"/></p><p>c 1 &lt; 2</p><ul><li>d</li></ul>"
+    );
+    assert_eq!(decode("&#8217;&#x41;&unknown;"), "’A&unknown;");
+    assert_eq!(escape("a<b & \"c\""), "a&lt;b &amp; &quot;c&quot;");
+}
@@ -0,0 +1,87 @@
+//! Here provide the parts of blog that do not depend on the browser,
+//! the parsing of files, the rendering of markdown and the paging of posts.
+//! they are compiled for both wasm and the native target,
+//! so they are tested with plain `cargo test`, the ones used by the app only
+//! are left out of the command line
+pub mod analytics;
+pub mod content;
+pub mod date;
+pub mod front_matter;
+#[cfg(any(target_arch = "wasm32", test))]
+pub mod fuzzy;
+pub mod markdown;
+pub mod markup;
+#[cfg(any(target_arch = "wasm32", test))]
+pub mod page;
+pub mod sanitize;
+pub mod seo;
+pub mod url;
+
+#[cfg(test)]
+mod tests {
+    use super::content::BlogMeta;
+    use super::front_matter::str2blog;
+    use super::markdown::to_html;
+    use std::fs;
+    use std::path::Path;
+
+    /// the paths of posts as in `markdown.index`, eg: "posts/2021-10-10-demo.rmd"
+    fn posts() -> Vec<String> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("posts");
+        let mut paths = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|e| e.ends_with(".rmd"))
+            .map(|e| format!("posts/{}", e))
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths
+    }
+
+    fn read(path: &str) -> String {
+        fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join(path)).unwrap()
+    }
+
+    #[test]
+    fn test_posts() {
+        let paths = posts();
+        assert!(!paths.is_empty());
+        let index = read("posts/markdown.index");
+        for path in paths {
+            assert!(
+                index.lines().any(|e| e.trim() == path),
+                "{} is not in markdown.index",
+                path
+            );
+            let meta = BlogMeta::from_path(&path).expect(&path);
+            assert!(!meta.title.is_empty() && meta.timestamp > 0, "{}", path);
+            let blog = str2blog(&read(&path), &meta).expect(&path);
+            assert!(!blog.ignored, "{}", path);
+            // the link of post is the same as the one in list
+            assert_eq!(blog.meta.id, meta.id, "{}", path);
+            assert!(!blog.content.is_empty(), "{}", path);
+            for part in &blog.content {
+                assert_eq!(to_html(part).trim().is_empty(), part.trim().is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_front_matter() {
+        let meta = BlogMeta::from_path("posts/2021-10-10-demo.rmd").unwrap();
+        let blog = str2blog(
+            "---\ntitle: demo\npublished: true\nauthors: Alice, Bob\ntags:\n - a\n - b\nlang: fr\n---\nsummary\n<!--break-->\nbody",
+            &meta,
+        )
+        .unwrap();
+        assert_eq!(blog.authors, vec!["Alice", "Bob"]);
+        assert_eq!(blog.tags, vec!["a", "b"]);
+        assert_eq!(blog.lang, "fr");
+        assert!(blog.published);
+        assert_eq!(blog.content, vec!["summary", "body"]);
+
+        assert!(str2blog("no front matter", &meta).is_none());
+        assert!(str2blog("---\ntitle: demo\n---\nbody", &meta).is_none());
+        assert!(str2blog("---\ntitle: demo\npublished: yes\n---\nbody", &meta).is_none());
+    }
+}
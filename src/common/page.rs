@@ -0,0 +1,73 @@
+//! Here provide the ordering and pagination of posts
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// the order of post sort
+#[derive(PartialEq, Clone, Debug, Default)]
+pub enum Order {
+    #[default]
+    Dec,
+    Inc,
+    Hot,
+}
+
+/// sort the (id, timestamp) of blogs in the order,
+/// the latest first if `Dec`, and `Hot` without scores, see `sort_hot`
+pub fn sort(indexs: &mut [(u64, u64)], order: &Order) {
+    match order {
+        Order::Inc => indexs.sort_by_key(|e| e.1),
+        Order::Dec | Order::Hot => indexs.sort_by_key(|e| Reverse(e.1)),
+    }
+}
+
+/// sort the (id, timestamp) of blogs by the scores of ids, the hottest first,
+/// the latest first among the ones of the same score
+pub fn sort_hot(indexs: &mut [(u64, u64)], scores: &HashMap<u64, u64>) {
+    let score = |id: &u64| scores.get(id).copied().unwrap_or(0);
+    indexs.sort_by(|a, b| score(&b.0).cmp(&score(&a.0)).then(b.1.cmp(&a.1)));
+}
+
+/// the number of pages that `len` items take
+pub fn total_pages(len: u64, per_page: u64) -> u64 {
+    if per_page == 0 {
+        return 0;
+    }
+    let num = len / per_page;
+    let remainder = len - per_page * num;
+    match remainder {
+        0 => num,
+        _ => num + 1,
+    }
+}
+
+/// the range of items on the pages from `first` to `last`,
+/// the pages start from 1 and the range is within `len`
+pub fn page_range(first: u64, last: u64, per_page: u64, len: usize) -> Range<usize> {
+    let start = ((first.max(1) - 1) * per_page).min(len as u64) as usize;
+    let end = (last * per_page).min(len as u64) as usize;
+    start..end.max(start)
+}
+
+#[test]
+fn test_pages() {
+    assert_eq!(total_pages(0, 12), 0);
+    assert_eq!(total_pages(12, 12), 1);
+    assert_eq!(total_pages(13, 12), 2);
+    assert_eq!(total_pages(13, 0), 0);
+    assert_eq!(page_range(1, 1, 12, 13), 0..12);
+    assert_eq!(page_range(2, 2, 12, 13), 12..13);
+    assert_eq!(page_range(1, 2, 12, 13), 0..13);
+    assert_eq!(page_range(3, 3, 12, 13), 13..13);
+    assert_eq!(page_range(0, 0, 12, 13), 0..0);
+
+    let mut indexs = vec![(1, 20), (2, 10), (3, 30)];
+    sort(&mut indexs, &Order::Dec);
+    assert_eq!(indexs, vec![(3, 30), (1, 20), (2, 10)]);
+    sort(&mut indexs, &Order::Inc);
+    assert_eq!(indexs, vec![(2, 10), (1, 20), (3, 30)]);
+    sort(&mut indexs, &Order::Hot);
+    assert_eq!(indexs, vec![(3, 30), (1, 20), (2, 10)]);
+    sort_hot(&mut indexs, &vec![(2, 5)].into_iter().collect());
+    assert_eq!(indexs, vec![(2, 10), (3, 30), (1, 20)]);
+}
@@ -0,0 +1,309 @@
+//! Here provide the sanitizing of rendered HTML before it is inserted into DOM
+//! only the tags, attributes and URL schemes allowed are kept, so the raw
+//! `<script>`, `onerror=` or `javascript:` in posts are never live
+use super::markup::{parse_html, Element, Node};
+use serde::Deserialize;
+use std::path::Path;
+
+/// the tags allowed, the others are unwrapped with their content kept
+const TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "caption",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "dd",
+    "del",
+    "details",
+    "dfn",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "input",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "samp",
+    "section",
+    "small",
+    "span",
+    "strike",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "tr",
+    "u",
+    "ul",
+    "var",
+];
+/// the tags removed along with their content, unless they are allowed
+const DROPPED: &[&str] = &[
+    "script", "style", "iframe", "frame", "frameset", "object", "embed", "applet", "noscript",
+    "template", "title", "textarea", "select", "button", "form", "svg", "math", "base", "link",
+    "meta",
+];
+/// the attributes allowed on all tags
+const ATTRIBUTES: &[&str] = &["id", "class", "title", "lang", "dir", "align"];
+/// the attributes allowed on the tags
+const TAG_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("a", &["href", "name", "rel"]),
+    ("img", &["src", "alt", "width", "height", "loading"]),
+    ("ol", &["start", "reversed", "type"]),
+    ("li", &["value"]),
+    ("td", &["colspan", "rowspan"]),
+    ("th", &["colspan", "rowspan", "scope"]),
+    ("col", &["span"]),
+    ("colgroup", &["span"]),
+    ("time", &["datetime"]),
+    ("details", &["open"]),
+    ("q", &["cite"]),
+    ("blockquote", &["cite"]),
+    ("del", &["cite", "datetime"]),
+    ("ins", &["cite", "datetime"]),
+    // the task lists rendered by markdown
+    ("input", &["type", "checked", "disabled"]),
+];
+/// the attributes holding urls, their schemes are checked
+const URL_ATTRIBUTES: &[&str] = &["href", "src", "cite"];
+const SCHEMES: &[&str] = &["http", "https", "mailto", "tel", "ftp"];
+
+/// how the HTML of posts is sanitized, `[sanitize]` in `site.toml`
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SanitizeConfig {
+    /// false keeps the HTML as it is, for the sites whose posts are all trusted
+    pub enabled: bool,
+    /// the tags allowed besides the defaults, eg: "iframe"
+    pub tags: Vec<String>,
+    /// the attributes allowed besides the defaults, "<tag>:<attribute>",
+    /// or "*:<attribute>" for all tags, eg: "iframe:src"
+    pub attributes: Vec<String>,
+    /// the posts shown as they are, by their file names without `.rmd`,
+    /// eg: "2021-10-10-demo", they are listed here rather than in the front
+    /// matter so that only the maintainers of `site.toml` trust a post
+    pub trusted: Vec<String>,
+}
+
+impl Default for SanitizeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            tags: vec![],
+            attributes: vec![],
+            trusted: vec![],
+        }
+    }
+}
+
+impl SanitizeConfig {
+    fn allows_tag(&self, tag: &str) -> bool {
+        TAGS.contains(&tag) || self.tags.iter().any(|e| e == tag)
+    }
+
+    fn allows_attribute(&self, tag: &str, name: &str) -> bool {
+        ATTRIBUTES.contains(&name)
+            || TAG_ATTRIBUTES
+                .iter()
+                .any(|(t, names)| *t == tag && names.contains(&name))
+            || self.attributes.iter().any(|e| match e.split_once(':') {
+                Some((t, n)) => (t == tag || t == "*") && n == name,
+                None => false,
+            })
+    }
+
+    /// the post at path is listed in `trusted`, so it is not sanitized
+    pub fn trusts(&self, path: &Path) -> bool {
+        let stem = path.file_stem().unwrap_or_default();
+        self.trusted.iter().any(|e| stem == e.as_str())
+    }
+
+    /// the problems of config, the invalid ones are removed
+    pub fn validate(&mut self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let len = self.attributes.len();
+        self.attributes.retain(|e| e.split_once(':').is_some());
+        if self.attributes.len() != len {
+            errors.push(format!(
+                "{} of sanitize `attributes` ignored, expected \"<tag>:<attribute>\"",
+                len - self.attributes.len()
+            ));
+        }
+        // the scripts are never allowed, the posts with them are listed in `trusted`
+        if self.tags.iter().any(|e| e == "script")
+            || self.attributes.iter().any(|e| e.contains(":on"))
+        {
+            errors.push(
+                "the scripts are not allowed by sanitize, list the posts in `trusted` instead"
+                    .into(),
+            );
+            self.tags.retain(|e| e != "script");
+            self.attributes.retain(|e| !e.contains(":on"));
+        }
+        errors
+    }
+}
+
+/// whether the url is relative or of the schemes allowed, the images
+/// may be embedded as data
+pub fn safe_url(url: &str, tag: &str) -> bool {
+    // browsers ignore the whitespaces and control characters in schemes
+    let url = url
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_lowercase();
+    let scheme = match url.find(|c| [':', '/', '?', '#'].contains(&c)) {
+        Some(pos) if url[pos..].starts_with(':') => &url[..pos],
+        _ => return true,
+    };
+    SCHEMES.contains(&scheme)
+        || (tag == "img"
+            && [
+                "data:image/png",
+                "data:image/jpeg",
+                "data:image/gif",
+                "data:image/webp",
+            ]
+            .iter()
+            .any(|e| url.starts_with(e)))
+}
+
+/// the element cleaned, what is removed is recorded
+fn clean(element: &mut Element, config: &SanitizeConfig, removed: &mut Vec<String>) {
+    let children = std::mem::take(&mut element.children);
+    for child in children {
+        let mut e = match child {
+            Node::Element(e) => e,
+            Node::Text(text) => {
+                element.children.push(Node::Text(text));
+                continue;
+            }
+            Node::Comment(_) => continue,
+        };
+        let allowed =
+            config.allows_tag(&e.name) && (e.name != "input" || e.attr("type") == Some("checkbox"));
+        if !allowed && (DROPPED.contains(&e.name.as_str()) || e.name == "input") {
+            removed.push(format!("<{}>", e.name));
+            continue;
+        }
+        clean(&mut e, config, removed);
+        if !allowed {
+            removed.push(format!("<{}>", e.name));
+            element.children.append(&mut e.children);
+            continue;
+        }
+        let tag = e.name.clone();
+        e.attrs.retain(|(name, value)| {
+            let name = name.to_lowercase();
+            let keep = config.allows_attribute(&tag, &name)
+                && (!URL_ATTRIBUTES.contains(&name.as_str()) || safe_url(value, &tag));
+            if !keep {
+                removed.push(format!("{}=\"{}\" of <{}>", name, value, tag));
+            }
+            keep
+        });
+        element.children.push(Node::Element(e));
+    }
+}
+
+/// the HTML sanitized, and what is removed from it, eg: `<script>`
+pub fn sanitize_report(html: &str, config: &SanitizeConfig) -> (String, Vec<String>) {
+    if !config.enabled {
+        return (html.to_string(), vec![]);
+    }
+    let mut root = parse_html(html);
+    let mut removed = Vec::new();
+    clean(&mut root, config, &mut removed);
+    let mut s = String::with_capacity(html.len());
+    root.write_children(&mut s, false);
+    (s, removed)
+}
+
+/// the HTML sanitized
+#[cfg(any(target_arch = "wasm32", test))]
+pub fn sanitize(html: &str, config: &SanitizeConfig) -> String {
+    let (html, removed) = sanitize_report(html, config);
+    if !removed.is_empty() {
+        log::warn!("removed from html: {}", removed.join(", "));
+    }
+    html
+}
+
+#[test]
+fn test_sanitize() {
+    let config = SanitizeConfig::default();
+    let (html, removed) = sanitize_report(
+        "<p onclick=\"x()\">a <b>b</b><script>alert(1)</script><img src=\"x\" onerror=\"alert(1)\"></p><a href=\" JaVa\tscript:alert(1)\">c</a><a href=\"https://a.io\">d</a><blink>e</blink><input type=\"checkbox\" checked disabled><input type=\"text\">",
+        &config,
+    );
+    assert_eq!(
+        html,
+        "<p>a <b>b</b><img src=\"x\"></p><a>c</a><a href=\"https://a.io\">d</a>e<input type=\"checkbox\" checked=\"\" disabled=\"\">"
+    );
+    assert_eq!(removed.len(), 6);
+    // the entities are decoded once and escaped back, so they are shown as written
+    let html = super::markdown::to_html("`&amp;` `&lt;div&gt;` &lt;b&gt; &amp;lt;");
+    assert_eq!(sanitize(&html, &config), html);
+    assert!(html.contains("<code>&amp;amp;</code>") && html.contains("&amp;lt;"));
+    assert!(safe_url("/posts/1/a#b", "a") && safe_url("mailto:a@b.c", "a"));
+    assert!(!safe_url("data:text/html,x", "a") && safe_url("data:image/png;base64,x", "img"));
+
+    let mut config = SanitizeConfig {
+        tags: vec!["iframe".into(), "script".into()],
+        attributes: vec!["iframe:src".into(), "iframe:onload".into(), "bad".into()],
+        ..Default::default()
+    };
+    assert_eq!(config.validate().len(), 2);
+    assert_eq!(
+        sanitize(
+            "<iframe src=\"https://a.io\" onload=\"x()\"></iframe>",
+            &config
+        ),
+        "<iframe src=\"https://a.io\"></iframe>"
+    );
+    let config = SanitizeConfig {
+        trusted: vec!["2021-10-10-demo".into()],
+        ..Default::default()
+    };
+    assert!(config.trusts(Path::new("posts/2021-10-10-demo.rmd")));
+    assert!(!config.trusts(Path::new("posts/2021-10-10-demo-2.rmd")));
+    let config = SanitizeConfig {
+        enabled: false,
+        ..Default::default()
+    };
+    assert_eq!(
+        sanitize("<script>x</script>", &config),
+        "<script>x</script>"
+    );
+}
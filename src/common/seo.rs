@@ -0,0 +1,333 @@
+//! Here provide the metadata of pages for the document head,
+//! the title, description, canonical url, Open Graph and Twitter cards,
+//! and the `BlogPosting` JSON-LD of posts
+#[cfg(any(target_arch = "wasm32", test))]
+use super::content::Blog;
+#[cfg(any(target_arch = "wasm32", test))]
+use super::date::parse_date;
+#[cfg(any(target_arch = "wasm32", test))]
+use super::markdown::to_html;
+#[cfg(any(target_arch = "wasm32", test))]
+use super::markup::parse_html;
+use serde::Deserialize;
+
+/// the length of description made from the excerpt
+#[cfg(any(target_arch = "wasm32", test))]
+const DESCRIPTION_CHARS: usize = 160;
+
+/// how the pages are described, `[seo]` in `site.toml`
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SeoConfig {
+    /// the title of pages, `{title}` and `{site}` are replaced
+    pub title_template: String,
+    /// the image shared for the pages and posts without `cover`
+    pub image: String,
+    /// the twitter account of the site, eg: "@homelyguy"
+    pub twitter: String,
+}
+
+impl Default for SeoConfig {
+    fn default() -> Self {
+        Self {
+            title_template: "{title} | {site}".into(),
+            image: "".into(),
+            twitter: "".into(),
+        }
+    }
+}
+
+/// the site a page belongs to
+#[cfg(any(target_arch = "wasm32", test))]
+pub struct Site<'a> {
+    pub name: &'a str,
+    pub description: &'a str,
+    /// the absolute url of the site, eg: "https://homelyguy.github.io/noer/"
+    pub url: &'a str,
+    /// the logo of the publisher of posts, a path under the site or url
+    pub logo: &'a str,
+    pub config: &'a SeoConfig,
+}
+
+/// an author of post, the url is the page of the author
+#[cfg(any(target_arch = "wasm32", test))]
+pub struct Person {
+    pub name: String,
+    pub url: String,
+}
+
+/// the metadata of a page in document head
+#[cfg(any(target_arch = "wasm32", test))]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Head {
+    pub title: String,
+    /// `<meta>` of the page, (attribute, key, content),
+    /// eg: ("property", "og:title", "demo")
+    pub meta: Vec<(&'static str, String, String)>,
+    /// the absolute url of the page
+    pub canonical: String,
+    /// the JSON-LD of the page, empty if none
+    pub json_ld: String,
+}
+
+/// the plain text of HTML, the whitespaces collapsed
+#[cfg(any(target_arch = "wasm32", test))]
+pub fn plain_text(html: &str) -> String {
+    parse_html(html)
+        .text()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// cut the text at a word boundary within `max` chars
+#[cfg(any(target_arch = "wasm32", test))]
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let cut = text
+        .char_indices()
+        .nth(max - 1)
+        .map(|(i, _)| i)
+        .unwrap_or(text.len());
+    let head = &text[..cut];
+    // the texts without spaces, eg: chinese, are cut at the char
+    let head = match head.rfind(' ') {
+        Some(pos) if pos > cut / 2 => &head[..pos],
+        _ => head,
+    };
+    format!(
+        "{}…",
+        head.trim_end_matches(|c: char| c.is_ascii_punctuation())
+    )
+}
+
+/// the description of blog, from front matter or the excerpt
+#[cfg(any(target_arch = "wasm32", test))]
+pub fn description(blog: &Blog) -> String {
+    if !blog.description.is_empty() {
+        return blog.description.clone();
+    }
+    let excerpt = blog.content.first().map(|e| to_html(e)).unwrap_or_default();
+    truncate(&plain_text(&excerpt), DESCRIPTION_CHARS)
+}
+
+/// the date of post in ISO 8601, eg: "2021-10-10T08:05:00Z"
+#[cfg(any(target_arch = "wasm32", test))]
+pub fn iso_date(date: &str) -> String {
+    let [year, month, day, hour, minute, second] = parse_date(date);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month.clamp(1, 12),
+        day.clamp(1, 31),
+        hour,
+        minute,
+        second
+    )
+}
+
+/// the absolute url of path under the site, the urls are kept
+#[cfg(any(target_arch = "wasm32", test))]
+pub fn absolute(site_url: &str, path: &str) -> String {
+    if path.is_empty() || path.contains("://") || path.starts_with("data:") {
+        return path.to_string();
+    }
+    format!(
+        "{}/{}",
+        site_url.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    )
+}
+
+#[cfg(any(target_arch = "wasm32", test))]
+impl Head {
+    /// the head of a page, eg: the list of posts,
+    /// an empty title is the site itself
+    pub fn page(site: &Site, title: &str, description: &str, url: &str) -> Self {
+        let title = match title.is_empty() {
+            true => site.name.to_string(),
+            false => site
+                .config
+                .title_template
+                .replace("{title}", title)
+                .replace("{site}", site.name),
+        };
+        let description = match description.is_empty() {
+            true => plain_text(site.description),
+            false => description.to_string(),
+        };
+        let image = absolute(site.url, &site.config.image);
+        let mut head = Self {
+            title: title.clone(),
+            meta: vec![],
+            canonical: url.to_string(),
+            json_ld: "".into(),
+        };
+        head.push("name", "description", &description);
+        head.push("property", "og:site_name", site.name);
+        head.push("property", "og:type", "website");
+        head.push("property", "og:title", &title);
+        head.push("property", "og:description", &description);
+        head.push("property", "og:url", url);
+        head.push("property", "og:image", &image);
+        let card = match image.is_empty() {
+            true => "summary",
+            false => "summary_large_image",
+        };
+        head.push("name", "twitter:card", card);
+        head.push("name", "twitter:site", &site.config.twitter);
+        head.push("name", "twitter:title", &title);
+        head.push("name", "twitter:description", &description);
+        head.push("name", "twitter:image", &image);
+        head
+    }
+
+    /// the head of post, an article with its authors and dates
+    pub fn post(site: &Site, blog: &Blog, authors: &[Person], url: &str) -> Self {
+        let title = blog.meta.title.replace('-', " ");
+        let description = description(blog);
+        let cover = match blog.cover.is_empty() {
+            true => &site.config.image,
+            false => &blog.cover,
+        };
+        let image = absolute(site.url, cover);
+        let mut head = Self::page(site, &title, &description, url);
+        head.set("property", "og:type", "article");
+        head.set("property", "og:image", &image);
+        head.set("name", "twitter:image", &image);
+        let card = match image.is_empty() {
+            true => "summary",
+            false => "summary_large_image",
+        };
+        head.set("name", "twitter:card", card);
+        let published = iso_date(&blog.meta.date);
+        head.push("property", "article:published_time", &published);
+        for author in authors {
+            head.push("property", "article:author", &author.name);
+        }
+        for tag in &blog.tags {
+            head.push("property", "article:tag", tag);
+        }
+        let mut json_ld = serde_json::json!({
+            "@context": "https://schema.org",
+            "@type": "BlogPosting",
+            "headline": title,
+            "description": description,
+            "datePublished": published,
+            "dateModified": published,
+            "mainEntityOfPage": { "@type": "WebPage", "@id": url },
+            "author": authors
+                .iter()
+                .map(|e| serde_json::json!({ "@type": "Person", "name": e.name, "url": e.url }))
+                .collect::<Vec<_>>(),
+            "publisher": {
+                "@type": "Organization",
+                "name": site.name,
+                "logo": { "@type": "ImageObject", "url": absolute(site.url, site.logo) },
+            },
+            "keywords": blog.tags.join(", "),
+        });
+        if !image.is_empty() {
+            json_ld["image"] = serde_json::Value::from(image);
+        }
+        if !blog.lang.is_empty() {
+            json_ld["inLanguage"] = serde_json::Value::from(blog.lang.clone());
+        }
+        // `</script>` in the texts must not close the script
+        head.json_ld = json_ld.to_string().replace("</", "<\\/");
+        head
+    }
+
+    /// add the meta, the empty ones are skipped
+    fn push(&mut self, attr: &'static str, key: &str, content: &str) {
+        if !content.is_empty() {
+            self.meta.push((attr, key.to_string(), content.to_string()));
+        }
+    }
+
+    /// replace the meta of key, it is removed if the content is empty
+    fn set(&mut self, attr: &'static str, key: &str, content: &str) {
+        self.meta.retain(|(_, k, _)| k != key);
+        self.push(attr, key, content);
+    }
+}
+
+#[test]
+fn test_seo() {
+    use super::content::BlogMeta;
+    use super::front_matter::parse_blog;
+
+    assert_eq!(iso_date("22-03-11-08-05"), "2022-03-11T08:05:00Z");
+    assert_eq!(
+        absolute("https://a.io/noer/", "/assets/a.png"),
+        "https://a.io/noer/assets/a.png"
+    );
+    assert_eq!(truncate("aaa bbb ccc", 9), "aaa bbb…");
+    assert_eq!(truncate("一二三四五", 3), "一二…");
+
+    let config = SeoConfig::default();
+    let site = Site {
+        name: "Noer",
+        description: "<strong>Hi</strong> there",
+        url: "https://a.io/noer/",
+        logo: "/assets/logo.png",
+        config: &config,
+    };
+    let head = Head::page(&site, "", "", "https://a.io/noer/");
+    assert_eq!(head.title, "Noer");
+    assert!(head
+        .meta
+        .contains(&("name", "description".into(), "Hi there".into())));
+    assert!(head
+        .meta
+        .contains(&("name", "twitter:card".into(), "summary".into())));
+
+    let meta = BlogMeta::from_path("posts/2021-10-10-demo-post.rmd").unwrap();
+    let blog = parse_blog(
+        "---\ntitle: demo\npublished: true\ntags: a, b\ncover: /assets/c.png\n---\nthe **excerpt**\n<!--break-->\nbody",
+        &meta,
+    )
+    .unwrap();
+    let authors = vec![Person {
+        name: "Alice".into(),
+        url: "https://a.io/noer/authors/alice".into(),
+    }];
+    let head = Head::post(
+        &site,
+        &blog,
+        &authors,
+        "https://a.io/noer/posts/1/demo-post",
+    );
+    assert_eq!(head.title, "demo post | Noer");
+    assert!(head
+        .meta
+        .contains(&("name", "description".into(), "the excerpt".into())));
+    assert!(head
+        .meta
+        .contains(&("property", "og:type".into(), "article".into())));
+    assert!(head.meta.contains(&(
+        "property",
+        "og:image".into(),
+        "https://a.io/noer/assets/c.png".into()
+    )));
+    assert!(head.meta.contains(&(
+        "property",
+        "article:published_time".into(),
+        "2021-10-10T00:00:00Z".into()
+    )));
+    let mut blog = blog;
+    blog.description = "a </script> b".into();
+    let head = Head::post(
+        &site,
+        &blog,
+        &authors,
+        "https://a.io/noer/posts/1/demo-post",
+    );
+    assert!(!head.json_ld.contains("</script>"));
+    let json: serde_json::Value = serde_json::from_str(&head.json_ld).unwrap();
+    assert_eq!(json["@type"], "BlogPosting");
+    assert_eq!(json["author"][0]["name"], "Alice");
+    assert_eq!(json["image"], "https://a.io/noer/assets/c.png");
+}
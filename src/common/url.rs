@@ -0,0 +1,50 @@
+//! Here provide the urls of routes, the app and the command line build
+//! them the same, so the links in the feed are the ones the app routes
+use super::content::BlogMeta;
+
+/// how the routes are represented in url
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Routing {
+    /// the path, eg: `/noer/posts/...`
+    History,
+    /// the fragment, eg: `/noer/#/posts/...`
+    Hash,
+}
+
+impl Routing {
+    pub fn from_name(name: &str) -> Self {
+        match name.trim().to_lowercase().as_str() {
+            "hash" => Self::Hash,
+            _ => Self::History,
+        }
+    }
+
+    /// the url of route under `base`, which ends with '/',
+    /// eg: "/noer/posts/123/title" or "/noer/#/posts/123/title"
+    pub fn url(self, base: &str, route: &str) -> String {
+        let route = route.trim_start_matches('/');
+        match self {
+            Self::History => format!("{}{}", base, route),
+            Self::Hash => format!("{}#/{}", base, route),
+        }
+    }
+}
+
+/// the route of post, eg: "posts/123/title"
+pub fn post_route(meta: &BlogMeta) -> String {
+    format!("posts/{}/{}", meta.id, meta.title)
+}
+
+#[test]
+fn test_url() {
+    let base = "https://a.io/noer/";
+    assert_eq!(
+        Routing::History.url(base, "/posts/1/a"),
+        "https://a.io/noer/posts/1/a"
+    );
+    assert_eq!(
+        Routing::Hash.url(base, "posts/1/a"),
+        "https://a.io/noer/#/posts/1/a"
+    );
+    assert_eq!(Routing::from_name(" Hash"), Routing::Hash);
+}
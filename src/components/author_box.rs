@@ -0,0 +1,59 @@
+use crate::components::social_links::SocialLinks;
+use crate::content::slugify;
+use crate::Route;
+use crate::SiteConfigContext;
+use yew::prelude::*;
+use yew_router::components::Link;
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    pub name: String,
+}
+
+/// the box that introduces the author,
+/// the profile is looked up in the authors registry of site config
+pub struct AuthorBox;
+
+impl Component for AuthorBox {
+    type Message = ();
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        let author = config.author(&ctx.props().name);
+        let avatar = if author.avatar.is_empty() {
+            html! {}
+        } else {
+            html! {
+                <div class="media-left">
+                    <figure class="image is-64x64">
+                        <img class="is-rounded" src={config.href(&author.avatar)} alt="author's avatar" />
+                    </figure>
+                </div>
+            }
+        };
+        html! {
+            <div class="box author-box">
+                <article class="media">
+                    { avatar }
+                    <div class="media-content">
+                        <Link<Route> classes={classes!("title", "is-5")} to={Route::Author { name: slugify(&author.name) }}>
+                            { &author.name }
+                        </Link<Route>>
+                        <p class="author-bio">{ &author.bio }</p>
+                        <div class="author-links">
+                            <SocialLinks links={author.links.clone()} />
+                        </div>
+                    </div>
+                </article>
+            </div>
+        }
+    }
+}
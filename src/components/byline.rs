@@ -0,0 +1,50 @@
+use crate::content::slugify;
+use crate::Route;
+use crate::SiteConfigContext;
+use yew::prelude::*;
+use yew_router::components::Link;
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    pub authors: Vec<String>,
+}
+
+/// the authors of a post, each links to the author page
+pub struct Byline;
+
+impl Component for Byline {
+    type Message = ();
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let authors = &ctx.props().authors;
+        if authors.is_empty() {
+            return html! {};
+        }
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        let last = authors.len() - 1;
+        let names = authors.iter().enumerate().map(|(ind, name)| {
+            let sep = match ind {
+                0 => "",
+                _ if ind == last => config.t("and"),
+                _ => ", ",
+            };
+            html! {
+                <>
+                    { sep }
+                    <Link<Route> to={Route::Author { name: slugify(name) }}>{ name }</Link<Route>>
+                </>
+            }
+        });
+        html! {
+            <span class="byline">{ config.t("by") }{ for names }</span>
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use crate::comments::{self, Comment, Thread};
+use crate::common::sanitize::{safe_url, SanitizeConfig};
+use crate::fetch::FetchError;
+use crate::markdown;
+use crate::pages::post::FetchState;
+use crate::BlogMeta;
+use crate::SiteConfigContext;
+use yew::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    pub meta: BlogMeta,
+}
+
+pub enum Msg {
+    Loaded(Result<Vec<Comment>, FetchError>),
+}
+
+/// the threaded comments at the end of a post
+pub struct Comments {
+    state: FetchState<Vec<Thread>>,
+}
+
+impl Component for Comments {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        match comments::provider(&config) {
+            Some(provider) => {
+                let future = provider.load(&ctx.props().meta);
+                ctx.link()
+                    .send_future(async move { Msg::Loaded(future.await) });
+                Self {
+                    state: FetchState::Fetching,
+                }
+            }
+            None => Self {
+                state: FetchState::NotFetching,
+            },
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        *self = Self::create(ctx);
+        true
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::Loaded(Ok(comments)) => {
+                self.state = FetchState::Success(comments::threads(comments));
+            }
+            Msg::Loaded(Err(err)) => {
+                log::error!("comments not loaded: {}", err);
+                self.state = FetchState::Failed(err);
+            }
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        let body = match &self.state {
+            FetchState::NotFetching => return html! {},
+            FetchState::Fetching => html! {},
+            FetchState::Success(threads) if threads.is_empty() => {
+                html! { <p>{ config.t("no_comments") }</p> }
+            }
+            FetchState::Success(threads) => {
+                html! { for threads.iter().map(|e| self.view_thread(e, &config.sanitize)) }
+            }
+            FetchState::Failed(_) => html! { <p>{ config.t("comments_not_loaded") }</p> },
+        };
+        let hint = if config.comments.hint.is_empty() {
+            html! {}
+        } else {
+            let hint = format!(
+                "<div>{}</div>",
+                markdown::sanitize(&markdown::to_html(&config.comments.hint), &config.sanitize)
+            );
+            html! {
+                <div class="comments-hint is-size-7">
+                    { for markdown::to_vnodes(&hint).unwrap_or_default() }
+                </div>
+            }
+        };
+        html! {
+            <section class="comments">
+                <h2 class="title is-4">{ config.t("comments") }</h2>
+                { body }
+                { hint }
+            </section>
+        }
+    }
+}
+
+impl Comments {
+    /// the comment and its replies, their html and links are sanitized
+    /// as they are written by the guests
+    fn view_thread(&self, thread: &Thread, sanitize: &SanitizeConfig) -> Html {
+        let Comment {
+            author,
+            url,
+            date,
+            body,
+            ..
+        } = &thread.comment;
+        // the url is written by the guest too, eg: `javascript:` is not linked
+        let author = if url.is_empty() || !safe_url(url, "a") {
+            html! { <strong>{ author }</strong> }
+        } else {
+            html! { <a href={url.clone()} target="_blank" rel="nofollow noopener"><strong>{ author }</strong></a> }
+        };
+        let output_div = format!(
+            "<div class = \"markdown-body\"> {} </div>",
+            markdown::sanitize(&markdown::to_html(body), sanitize)
+        );
+        let body = markdown::to_vnodes(&output_div).unwrap_or_default();
+        html! {
+            <article class="media">
+                <div class="media-content">
+                    <div class="content">
+                        <p>
+                            { author }
+                            { " " }
+                            <small><time datetime={date.clone()}>{ date }</time></small>
+                        </p>
+                        { for body }
+                    </div>
+                    { for thread.replies.iter().map(|e| self.view_thread(e, sanitize)) }
+                </div>
+            </article>
+        }
+    }
+}
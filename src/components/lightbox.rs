@@ -0,0 +1,136 @@
+use crate::SiteConfigContext;
+use wasm_bindgen::{prelude::Closure, JsCast};
+use web_sys::KeyboardEvent;
+use yew::prelude::*;
+
+/// an image of post shown in the lightbox
+#[derive(Clone, Debug, PartialEq)]
+pub struct LightboxImage {
+    pub src: String,
+    pub alt: String,
+    /// the caption of its figure, or its title
+    pub caption: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// the images of the post, in order
+    pub images: Vec<LightboxImage>,
+    /// the one zoomed into
+    pub index: usize,
+    pub onclose: Callback<()>,
+}
+
+pub enum Msg {
+    Prev,
+    Next,
+    Close,
+    Key(KeyboardEvent),
+}
+
+/// the image zoomed into over the page, the other images of the post
+/// are reached by the buttons or the arrow keys, and escape closes it
+pub struct Lightbox {
+    index: usize,
+    /// the keys are listened on window, so they work wherever the focus is
+    on_key: Closure<dyn FnMut(KeyboardEvent)>,
+}
+
+impl Component for Lightbox {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let callback = ctx.link().callback(Msg::Key);
+        let on_key = Closure::wrap(
+            Box::new(move |e: KeyboardEvent| callback.emit(e)) as Box<dyn FnMut(KeyboardEvent)>
+        );
+        if let Some(window) = web_sys::window() {
+            if let Err(e) =
+                window.add_event_listener_with_callback("keydown", on_key.as_ref().unchecked_ref())
+            {
+                log::error!("keys of lightbox not listened: {:?}", e);
+            }
+        }
+        Self {
+            index: ctx.props().index,
+            on_key,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let count = ctx.props().images.len().max(1);
+        match msg {
+            Msg::Prev => self.index = (self.index + count - 1) % count,
+            Msg::Next => self.index = (self.index + 1) % count,
+            Msg::Close => {
+                ctx.props().onclose.emit(());
+                return false;
+            }
+            Msg::Key(e) => {
+                let msg = match e.key().as_str() {
+                    "ArrowLeft" => Msg::Prev,
+                    "ArrowRight" => Msg::Next,
+                    "Escape" => Msg::Close,
+                    _ => return false,
+                };
+                e.prevent_default();
+                return self.update(ctx, msg);
+            }
+        }
+        true
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        self.index = ctx.props().index;
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        let images = &ctx.props().images;
+        let image = match images.get(self.index) {
+            Some(image) => image,
+            None => return html! {},
+        };
+        let close = ctx.link().callback(|_| Msg::Close);
+        let navigation = if images.len() > 1 {
+            html! {
+                <>
+                    <button class="button is-dark lightbox-prev" aria-label={config.t("previous_image")}
+                        onclick={ctx.link().callback(|_| Msg::Prev)}>{ "‹" }</button>
+                    <button class="button is-dark lightbox-next" aria-label={config.t("next_image")}
+                        onclick={ctx.link().callback(|_| Msg::Next)}>{ "›" }</button>
+                    <span class="lightbox-count">{ format!("{} / {}", self.index + 1, images.len()) }</span>
+                </>
+            }
+        } else {
+            html! {}
+        };
+        html! {
+            <div class="modal is-active lightbox" role="dialog" aria-modal="true">
+                <div class="modal-background" onclick={close.clone()}></div>
+                <figure class="modal-content lightbox-content">
+                    <img src={image.src.clone()} alt={image.alt.clone()} />
+                    { if image.caption.is_empty() { html! {} } else { html! {
+                        <figcaption>{ &image.caption }</figcaption>
+                    } } }
+                </figure>
+                { navigation }
+                <button class="modal-close is-large" aria-label={config.t("close")} onclick={close}></button>
+            </div>
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &Context<Self>) {
+        if let Some(window) = web_sys::window() {
+            let _ = window.remove_event_listener_with_callback(
+                "keydown",
+                self.on_key.as_ref().unchecked_ref(),
+            );
+        }
+    }
+}
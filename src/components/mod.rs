@@ -1,2 +1,7 @@
+pub mod author_box;
+pub mod byline;
+pub mod comments;
+pub mod lightbox;
 pub mod pagination;
 pub mod post_card;
+pub mod social_links;
@@ -4,6 +4,7 @@ use yew::prelude::*;
 use yew_router::prelude::*;
 
 use crate::Route;
+use crate::SiteConfigContext;
 
 const ELLIPSIS: &str = "\u{02026}";
 
@@ -37,7 +38,11 @@ impl Component for Pagination {
         );
         if ctx.props().page == 0 {
             log::debug!("no posts to pagination");
-            html! { {"No Page Found"} }
+            let (config, _) = ctx
+                .link()
+                .context::<SiteConfigContext>(Callback::noop())
+                .expect("Site Config Context not found");
+            html! { { config.t("no_page_found") } }
         } else {
             html! {
                 <>
@@ -1,6 +1,8 @@
+use crate::components::byline::Byline;
 use crate::BlogMeta;
 use crate::ParseActContext;
 use crate::Route;
+use crate::SiteConfigContext;
 use yew::prelude::*;
 use yew_router::components::Link;
 
@@ -12,7 +14,10 @@ pub struct Props {
 }
 
 pub struct BlogCard {
-    meta: BlogMeta,
+    /// None if there is no post of the id
+    meta: Option<BlogMeta>,
+    /// empty if they are not known without fetching the post
+    authors: Vec<String>,
 }
 
 impl Component for BlogCard {
@@ -29,8 +34,12 @@ impl Component for BlogCard {
             parser.len(),
             parser.inner().indexs
         );
-        let meta = parser.get_meta(&ctx.props().id).unwrap().clone();
-        Self { meta }
+        let meta = parser.find_meta(&ctx.props().id);
+        let authors = meta
+            .as_ref()
+            .map(|meta| Self::authors(ctx, meta))
+            .unwrap_or_default();
+        Self { meta, authors }
     }
 
     fn changed(&mut self, ctx: &Context<Self>) -> bool {
@@ -38,12 +47,26 @@ impl Component for BlogCard {
             .link()
             .context::<ParseActContext>(Callback::noop())
             .expect("Parser Context not found");
-        self.meta = parser.get_meta(&ctx.props().id).unwrap().clone();
+        let meta = parser.find_meta(&ctx.props().id);
+        if meta.as_ref().map(|e| e.id) != self.meta.as_ref().map(|e| e.id) {
+            self.authors = meta
+                .as_ref()
+                .map(|meta| Self::authors(ctx, meta))
+                .unwrap_or_default();
+        }
+        self.meta = meta;
         true
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
-        let Self { meta } = self;
+        let Self { meta, authors } = self;
+        let meta = match meta {
+            Some(meta) => meta,
+            None => {
+                log::debug!("no post of id {}", ctx.props().id);
+                return html! {};
+            }
+        };
         let display = &ctx.props().display;
         //let display = use_context::<post_list::Display>().expect("Display Not Found");
         if display == "gridCard" {
@@ -60,6 +83,8 @@ impl Component for BlogCard {
                         </Link<Route>>
                         <br />
                         <time datetime={ meta.date.clone() } >{ &meta.date }</time>
+                        <br />
+                        <Byline authors={authors.clone()} />
                     </div>
                 </div>
             }
@@ -81,6 +106,8 @@ impl Component for BlogCard {
                                     </div>
                                     <div class="level-item mt-2" style="display: block">
                                         <time datetime={ meta.date.clone() } >{ &meta.date }</time>
+                                        { " " }
+                                        <Byline authors={authors.clone()} />
                                     </div>
                                 </div>
                         </div>
@@ -94,3 +121,18 @@ impl Component for BlogCard {
         }
     }
 }
+impl BlogCard {
+    /// authors of the blog if they are known, the posts on the list
+    /// are not fetched for them, see `Parser::authors_of`
+    fn authors(ctx: &Context<Self>, meta: &BlogMeta) -> Vec<String> {
+        let (parser, _) = ctx
+            .link()
+            .context::<ParseActContext>(Callback::noop())
+            .expect("Parser Context not found");
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        parser.authors_of(meta, &config.admin).unwrap_or_default()
+    }
+}
@@ -0,0 +1,43 @@
+use crate::config::SocialLink;
+use crate::SiteConfigContext;
+use yew::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    pub links: Vec<SocialLink>,
+    /// class of each link, eg: `navbar-item` in the dropdown
+    #[prop_or_default]
+    pub class: String,
+}
+
+/// links of profiles with icons,
+/// `rel="me"` is set so that the profiles can verify the identity
+pub struct SocialLinks;
+
+impl Component for SocialLinks {
+    type Message = ();
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        let class = &ctx.props().class;
+        let links = ctx.props().links.iter().map(|link| {
+            html! {
+                <a class={classes!(class.clone(), "social-link")} href={link.href()} target="_blank" rel="me noopener">
+                    <span class="icon">
+                        <img src={config.href(&link.kind.icon())} alt={link.kind.name()} />
+                    </span>
+                    <span>{ link.label() }</span>
+                </a>
+            }
+        });
+        html! { for links }
+    }
+}
@@ -0,0 +1,563 @@
+//! Here provide the site configuration
+//! it is read from `site.toml` at startup, so changing the site info
+//! does not require to recompile, the values in `constant.rs` are
+//! the defaults for whatever is missing in the file
+use crate::common::analytics::AnalyticsConfig;
+use crate::common::content::slugify;
+use crate::common::sanitize::SanitizeConfig;
+use crate::common::seo::SeoConfig;
+use crate::common::url::Routing;
+use crate::constant;
+#[cfg(target_arch = "wasm32")]
+use crate::fetch::{fetch_cached, FetchError};
+use crate::i18n;
+use serde::Deserialize;
+use toml::value::{Table, Value};
+
+/// the mode of the site, the logger is silent in release mode
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    Development,
+    Release,
+}
+
+/// the names of routing in `site.toml`
+const ROUTINGS: &[&str] = &["history", "hash"];
+
+/// the kind of a social link, it decides the icon
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkKind {
+    Github,
+    Gitlab,
+    Mastodon,
+    Twitter,
+    Linkedin,
+    Email,
+    Rss,
+    #[serde(other)]
+    Website,
+}
+
+impl LinkKind {
+    pub fn from_name(name: &str) -> Self {
+        match name.trim().to_lowercase().as_str() {
+            "github" => Self::Github,
+            "gitlab" => Self::Gitlab,
+            "mastodon" => Self::Mastodon,
+            "twitter" => Self::Twitter,
+            "linkedin" => Self::Linkedin,
+            "email" => Self::Email,
+            "rss" => Self::Rss,
+            _ => Self::Website,
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Github => "github",
+            Self::Gitlab => "gitlab",
+            Self::Mastodon => "mastodon",
+            Self::Twitter => "twitter",
+            Self::Linkedin => "linkedin",
+            Self::Email => "email",
+            Self::Rss => "rss",
+            Self::Website => "website",
+        }
+    }
+
+    /// path of the icon under the site
+    #[cfg(target_arch = "wasm32")]
+    pub fn icon(&self) -> String {
+        format!("/assets/icons/{}.svg", self.name())
+    }
+}
+
+/// a profile of the user, eg: github, mastodon, email
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct SocialLink {
+    pub kind: LinkKind,
+    #[serde(default)]
+    pub label: String,
+    pub url: String,
+}
+
+impl SocialLink {
+    pub fn new(kind: &str, label: &str, url: &str) -> Self {
+        Self {
+            kind: LinkKind::from_name(kind),
+            label: label.into(),
+            url: url.into(),
+        }
+    }
+
+    /// the label to display, name of the kind if not specified
+    #[cfg(target_arch = "wasm32")]
+    pub fn label(&self) -> &str {
+        if self.label.trim().is_empty() {
+            self.kind.name()
+        } else {
+            &self.label
+        }
+    }
+
+    /// the href of link, email address is made into `mailto:`
+    #[cfg(target_arch = "wasm32")]
+    pub fn href(&self) -> String {
+        if self.kind == LinkKind::Email && !self.url.starts_with("mailto:") {
+            format!("mailto:{}", self.url)
+        } else {
+            self.url.clone()
+        }
+    }
+}
+
+/// the profile of an author in the registry
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Author {
+    pub name: String,
+    #[serde(default)]
+    pub avatar: String,
+    #[serde(default)]
+    pub bio: String,
+    #[serde(default)]
+    pub links: Vec<SocialLink>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Author {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            avatar: "".into(),
+            bio: "".into(),
+            links: vec![],
+        }
+    }
+}
+
+/// where the comments of posts come from
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CommentsConfig {
+    /// "static" reads them from a file per post in `dir`,
+    /// "none" disables the comments
+    pub provider: String,
+    pub dir: String,
+    /// how to leave a comment, eg: open a pull request
+    pub hint: String,
+}
+
+impl Default for CommentsConfig {
+    fn default() -> Self {
+        Self {
+            provider: constant::COMMENTS_PROVIDER.into(),
+            dir: constant::COMMENTS_DIR.into(),
+            hint: "".into(),
+        }
+    }
+}
+
+/// site infomation shared with all pages through the context
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SiteConfig {
+    pub admin: String,
+    pub site_name: String,
+    pub logo_pic: String,
+    pub avatar_pic: String,
+    pub site_description: String,
+    pub links: Vec<SocialLink>,
+    pub authors: Vec<Author>,
+    /// language of UI strings and posts without `lang`,
+    /// "auto" follows the language of browser
+    pub language: String,
+    pub comments: CommentsConfig,
+    /// how the HTML of posts is sanitized
+    pub sanitize: SanitizeConfig,
+    /// how the pages are described in document head
+    pub seo: SeoConfig,
+    /// where the page views and reads are sent
+    pub analytics: AnalyticsConfig,
+    pub subpath: String,
+    /// the url the site is deployed at, the links in feeds are made from it
+    pub url: String,
+    pub items_per_page: u64,
+    /// "pages" or "infinite" scroll
+    pub pagination: String,
+    /// "history" or "hash", see `Routing`
+    pub routing: String,
+    /// the markdown file shown in the page not found
+    pub not_found_page: String,
+    pub mode: Mode,
+    pub log_level: String,
+}
+
+impl Default for SiteConfig {
+    fn default() -> Self {
+        let (mode, level) = match constant::MODE {
+            constant::Modes::Development(level) => (Mode::Development, level),
+            constant::Modes::Release => (Mode::Release, log::Level::Error),
+        };
+        Self {
+            admin: constant::ADMIN.into(),
+            site_name: constant::SITE_NAME.into(),
+            logo_pic: constant::LOGO_PIC.into(),
+            avatar_pic: constant::AVATR_PIC.into(),
+            site_description: constant::SITE_DESCRIPTION.into(),
+            links: constant::LINKS
+                .iter()
+                .map(|(kind, label, url)| SocialLink::new(kind, label, url))
+                .collect(),
+            authors: vec![],
+            language: constant::LANGUAGE.into(),
+            comments: CommentsConfig::default(),
+            sanitize: SanitizeConfig::default(),
+            seo: SeoConfig::default(),
+            analytics: AnalyticsConfig::default(),
+            subpath: constant::SUBPATH.into(),
+            url: constant::SITE_URL.into(),
+            items_per_page: constant::ITEMS_PER_PAGE,
+            pagination: constant::PAGINATION.into(),
+            routing: constant::ROUTING.into(),
+            not_found_page: constant::NOT_FOUND_PAGE.into(),
+            mode,
+            log_level: level.to_string().to_lowercase(),
+        }
+    }
+}
+
+impl SiteConfig {
+    /// parse the content of `site.toml`,
+    /// the problems found are returned along with the config
+    /// and the default value is used in place of invalid one
+    pub fn from_toml(s: &str) -> (Self, Vec<String>) {
+        let mut table = match toml::from_str::<Table>(s) {
+            Ok(table) => table,
+            Err(e) => {
+                return (
+                    Self::default(),
+                    vec![format!("site.toml is not valid: {}", e)],
+                )
+            }
+        };
+        let mut errors = remove_invalid(&mut table);
+        match Value::Table(table).try_into::<Self>() {
+            Ok(mut config) => {
+                errors.extend(config.validate());
+                (config, errors)
+            }
+            Err(e) => {
+                errors.push(format!("site.toml is not valid: {}", e));
+                (Self::default(), errors)
+            }
+        }
+    }
+
+    /// check the values and fallback to default if invalid
+    pub fn validate(&mut self) -> Vec<String> {
+        let default = Self::default();
+        let mut errors = Vec::new();
+        if self.site_name.trim().is_empty() {
+            errors.push("`site_name` is empty".to_string());
+            self.site_name = default.site_name.clone();
+        }
+        if self.items_per_page == 0 {
+            errors.push(format!(
+                "`items_per_page` must be greater than 0, {} is used",
+                default.items_per_page
+            ));
+            self.items_per_page = default.items_per_page;
+        } else if !self.items_per_page.is_multiple_of(3) {
+            errors.push(format!(
+                "`items_per_page` shall be multiple of 3 to fill the grid view, got {}",
+                self.items_per_page
+            ));
+        }
+        if !["pages", "infinite"].contains(&self.pagination.as_str()) {
+            errors.push(format!(
+                "`pagination` {:?} is unknown, expected pages or infinite",
+                self.pagination
+            ));
+            self.pagination = default.pagination.clone();
+        }
+        if !ROUTINGS.contains(&self.routing.as_str()) {
+            errors.push(format!(
+                "`routing` {:?} is unknown, expected {}",
+                self.routing,
+                ROUTINGS.join(" or ")
+            ));
+            self.routing = default.routing.clone();
+        }
+        let len = self.links.len();
+        self.links.retain(|e| !e.url.trim().is_empty());
+        if self.links.len() != len {
+            errors.push(format!(
+                "{} of `links` without `url` ignored",
+                len - self.links.len()
+            ));
+        }
+        let len = self.authors.len();
+        self.authors.retain(|e| !slugify(&e.name).is_empty());
+        if self.authors.len() != len {
+            errors.push(format!(
+                "{} of `authors` without `name` ignored",
+                len - self.authors.len()
+            ));
+        }
+        if self.language != "auto" && !i18n::is_supported(&self.language) {
+            errors.push(format!(
+                "`language` {:?} has no translation, english is used for UI strings",
+                self.language
+            ));
+        }
+        if !["static", "none"].contains(&self.comments.provider.as_str()) {
+            errors.push(format!(
+                "comments `provider` {:?} is unknown, expected static or none",
+                self.comments.provider
+            ));
+            self.comments.provider = "none".into();
+        }
+        errors.extend(self.sanitize.validate());
+        errors.extend(self.analytics.validate());
+        if !self.seo.title_template.contains("{title}") {
+            errors.push(format!(
+                "seo `title_template` {:?} has no {{title}}, {:?} is used",
+                self.seo.title_template, default.seo.title_template
+            ));
+            self.seo.title_template = default.seo.title_template.clone();
+        }
+        if self.log_level.parse::<log::Level>().is_err() {
+            errors.push(format!(
+                "`log_level` {:?} is unknown, expected one of error, warn, info, debug, trace",
+                self.log_level
+            ));
+            self.log_level = default.log_level.clone();
+        }
+        self.subpath = normalize_subpath(&self.subpath);
+        if !self.url.is_empty() && !self.url.starts_with("http") {
+            errors.push(format!(
+                "`url` {:?} shall start with http:// or https://",
+                self.url
+            ));
+            self.url = default.url.clone();
+        }
+        if !self.url.is_empty() && !self.url.ends_with('/') {
+            self.url.push('/');
+        }
+        errors
+    }
+
+    /// the sub-path the site is served takes the place of the configured one,
+    /// which is an error if they differ
+    #[cfg(target_arch = "wasm32")]
+    pub fn serve_subpath(&mut self) -> Option<String> {
+        let served = served_subpath();
+        let error = if self.subpath != served {
+            Some(format!(
+                "`subpath` {:?} differs from the sub-path {:?} the site is served, rebuild the site with it",
+                self.subpath, served
+            ))
+        } else {
+            None
+        };
+        self.subpath = served;
+        error
+    }
+
+    /// look up the author in the registry,
+    /// `admin` is made from the site info if not registered
+    #[cfg(target_arch = "wasm32")]
+    pub fn author(&self, name: &str) -> Author {
+        let slug = slugify(name);
+        if let Some(author) = self.authors.iter().find(|e| slugify(&e.name) == slug) {
+            return author.clone();
+        }
+        if slugify(&self.admin) == slug {
+            return Author {
+                name: self.admin.clone(),
+                avatar: self.avatar_pic.clone(),
+                bio: "".into(),
+                links: self.links.clone(),
+            };
+        }
+        Author::new(name)
+    }
+
+    /// make "auto" language into the one of browser
+    /// if there is translation for it
+    #[cfg(target_arch = "wasm32")]
+    pub fn resolve_language(&mut self) {
+        if self.language == "auto" {
+            self.language = i18n::navigator_language()
+                .filter(|e| i18n::is_supported(e))
+                .map(|e| i18n::primary(&e))
+                .unwrap_or_else(|| i18n::DEFAULT_LANG.into());
+        }
+    }
+
+    /// whether the next page is loaded once scrolled to the bottom
+    #[cfg(target_arch = "wasm32")]
+    pub fn infinite_scroll(&self) -> bool {
+        self.pagination == "infinite"
+    }
+
+    /// how the routes are represented in url
+    pub fn routing(&self) -> Routing {
+        Routing::from_name(&self.routing)
+    }
+
+    /// UI string of the key in site language
+    pub fn t(&self, key: &str) -> &'static str {
+        i18n::t(&self.language, key)
+    }
+
+    /// set the max level of logger
+    #[cfg(target_arch = "wasm32")]
+    pub fn apply_log_level(&self) {
+        let level = match self.mode {
+            Mode::Release => log::LevelFilter::Off,
+            Mode::Development => self
+                .log_level
+                .parse::<log::Level>()
+                .map(|e| e.to_level_filter())
+                .unwrap_or(log::LevelFilter::Trace),
+        };
+        log::set_max_level(level);
+    }
+
+    /// absolute href of `path` under the sub-path
+    #[cfg(target_arch = "wasm32")]
+    pub fn href(&self, path: &str) -> String {
+        let path = path.trim_start_matches('/');
+        if self.subpath == "/" {
+            format!("/{}", path)
+        } else {
+            format!("/{}{}", self.subpath, path)
+        }
+    }
+}
+
+/// normalize the sub-path into the form of `constant::SUBPATH`
+/// that is "/" or "sub-path/"
+pub fn normalize_subpath(s: &str) -> String {
+    let s = s.trim().trim_matches('/');
+    if s.is_empty() {
+        "/".into()
+    } else {
+        format!("{}/", s)
+    }
+}
+
+/// the sub-path the site is served
+/// trunk writes it into `<base href>` from `--public-url`,
+/// `constant::SUBPATH` is used if not found
+#[cfg(target_arch = "wasm32")]
+pub fn served_subpath() -> String {
+    web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.query_selector("base[href]").ok().flatten())
+        .and_then(|e| e.get_attribute("href"))
+        .map(|href| normalize_subpath(&href))
+        .unwrap_or_else(|| normalize_subpath(constant::SUBPATH))
+}
+
+/// remove the values of `site.toml` that are not valid, so a bad one,
+/// eg: a typo in `mode`, does not reset the others to the defaults,
+/// the values in a section, eg: `[sanitize]`, are checked one by one
+fn remove_invalid(table: &mut Table) -> Vec<String> {
+    // the error of config with the value only
+    let check = |key: &str, value: Value| {
+        let mut single = Table::new();
+        single.insert(key.to_string(), value);
+        Value::Table(single).try_into::<SiteConfig>().err()
+    };
+    let mut errors = Vec::new();
+    let keys = table.keys().cloned().collect::<Vec<_>>();
+    for key in keys {
+        if let Some(Value::Table(section)) = table.get_mut(&key) {
+            let names = section.keys().cloned().collect::<Vec<_>>();
+            for name in names {
+                let mut single = Table::new();
+                single.insert(name.clone(), section[&name].clone());
+                if let Some(e) = check(&key, Value::Table(single)) {
+                    errors.push(format!("`{}.{}` is ignored: {}", key, name, e));
+                    section.remove(&name);
+                }
+            }
+        }
+        if let Some(e) = check(&key, table[&key].clone()) {
+            errors.push(format!("`{}` is ignored: {}", key, e));
+            table.remove(&key);
+        }
+    }
+    errors
+}
+
+/// the absolute url of `path` under the site
+#[cfg(target_arch = "wasm32")]
+pub fn site_url(path: &str) -> String {
+    let origin = web_sys::window()
+        .and_then(|w| w.location().origin().ok())
+        .unwrap_or_default();
+    let path = path.trim_start_matches('/');
+    match served_subpath().as_str() {
+        "/" => format!("{}/{}", origin, path),
+        subpath => format!("{}/{}{}", origin, subpath, path),
+    }
+}
+
+/// load `site.toml` from the root of the site
+/// a missing file is not an error, the defaults are used
+#[cfg(target_arch = "wasm32")]
+pub async fn load_config() -> (SiteConfig, Vec<String>) {
+    let url = site_url("site.toml");
+    log::debug!("{}", &format!("config url: {:?}", url));
+    match fetch_cached(&url).await {
+        Ok(text) => {
+            let (mut config, mut errors) = SiteConfig::from_toml(&text);
+            errors.extend(config.serve_subpath());
+            (config, errors)
+        }
+        Err(FetchError::NotFound(_)) => {
+            log::info!("site.toml not found, the default config is used");
+            let config = SiteConfig {
+                subpath: served_subpath(),
+                ..Default::default()
+            };
+            (config, vec![])
+        }
+        Err(e) => (
+            SiteConfig::default(),
+            vec![format!("site.toml is not fetched: {}", e)],
+        ),
+    }
+}
+
+#[test]
+fn test_routing() {
+    let (config, errors) = SiteConfig::from_toml("site_name = \"demo\"\nrouting = \"Hash\"");
+    assert_eq!(config.site_name, "demo");
+    assert_eq!(config.routing(), Routing::History);
+    assert_eq!(errors.len(), 1);
+    let (config, errors) = SiteConfig::from_toml("routing = \"hash\"");
+    assert_eq!(config.routing(), Routing::Hash);
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn test_from_toml() {
+    let (config, errors) = SiteConfig::from_toml(
+        "site_name = \"demo\"\nmode = \"debug\"\n[sanitize]\nenabled = \"no\"\ntags = [\"iframe\"]",
+    );
+    assert_eq!(config.site_name, "demo");
+    assert_eq!(config.mode, SiteConfig::default().mode);
+    assert!(config.sanitize.enabled);
+    assert_eq!(config.sanitize.tags, vec!["iframe"]);
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].starts_with("`mode`") && errors[1].starts_with("`sanitize.enabled`"));
+    let (_, errors) = SiteConfig::from_toml("site_name = ");
+    assert_eq!(errors.len(), 1);
+}
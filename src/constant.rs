@@ -1,19 +1,46 @@
 #![allow(dead_code)]
+//! the default values of site config
+//! they are used when `site.toml` is missing or the key is not specified
 
 pub enum Modes {
     Development(log::Level),
     Release,
 }
 pub const ITEMS_PER_PAGE: u64 = 12;
+// "pages" or "infinite" scroll
+pub const PAGINATION: &str = "pages";
+// "history" routes by the path, which requires the 404.html fallback
+// on static hosts, "hash" routes by the fragment, eg: `#/posts/...`
+pub const ROUTING: &str = "history";
+// the markdown shown in the page not found if the file exists
+pub const NOT_FOUND_PAGE: &str = "posts/404.md";
+// number of posts suggested in the page not found
+pub const SUGGESTIONS: usize = 3;
 pub const MODE: Modes = Modes::Development(log::Level::Trace);
+// times to retry the failed fetching, eg: network error, 5xx status
+// and the delay before the first retry, doubled each time
+pub const FETCH_RETRIES: u32 = 3;
+pub const FETCH_BACKOFF_MS: i32 = 500;
+// the max size of the files cached in localStorage,
+// which is about 5MB in most browsers
+pub const CACHE_MAX_BYTES: usize = 4 * 1024 * 1024;
 
 // the Default value
 //pub const SUBPATH: &str = "/";
 pub const SUBPATH: &str = "noer/";
+// the url the site is deployed at, including the sub-path,
+// set in site.toml, the links of feed and pages are not made without it
+pub const SITE_URL: &str = "";
 
 // site info
 pub const ADMIN: &str = "Bruce Yuan";
 pub const SITE_NAME: &str = "Homely See Around";
+// comments of posts are read from a json file per post in the directory
+// "static" or "none" to disable
+pub const COMMENTS_PROVIDER: &str = "static";
+pub const COMMENTS_DIR: &str = "posts/comments/";
+// language of UI strings, "auto" follows the browser
+pub const LANGUAGE: &str = "auto";
 pub const LOGO_PIC: &str = "/assets/logo.png";
 pub const AVATR_PIC: &str = "/assets/github.png";
 pub const SITE_DESCRIPTION: &str = "<strong>Welcome everybody,</strong> glad to see you here!
@@ -21,13 +48,11 @@ Here are awesome blogs of my <a href=\"https://github.com/homelyguy\">website</a
 enjoy yourself here.
   ";
 
-// Note That
-// the string is line-separated
-// each line is a key-value pair
-pub const USER_INFO: &str = "
-github: https://github.com/homelyguy/,
-email: bruceyuan0@protonmail.com,
-social_platform: https://yourlink.here/
-";
-// or the alternative
-pub const OTHER_USER_INFO: &str = "github: https://github.com/homelyguy/,\nemail: bruceyuan0@protonmail.com,\nsocial_platform: https://yourlink.here/,";
+// links of the user present to viewers
+// each one is (kind, label, url), the known kinds are
+// github, gitlab, mastodon, twitter, linkedin, email, rss, website
+pub const LINKS: &[(&str, &str, &str)] = &[
+    ("github", "github", "https://github.com/homelyguy/"),
+    ("email", "email", "bruceyuan0@protonmail.com"),
+    ("website", "social_platform", "https://yourlink.here/"),
+];
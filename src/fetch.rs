@@ -0,0 +1,186 @@
+//! Here provide the fetching of site resources
+//! the response status is checked and transient failures are retried,
+//! the files of site are cached to revisit instantly or offline
+use crate::cache::{self, Entry};
+use crate::constant;
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+
+/// Something wrong has occurred while fetching an external resource.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FetchError {
+    /// the request failed without response, eg: offline
+    Network(String),
+    /// the server responded with an unsuccessful status
+    Http { status: u16, url: String },
+    /// the content is not in the expected format
+    Parse(String),
+    /// the resource does not exist
+    NotFound(String),
+}
+
+impl FetchError {
+    /// whether it is worth to try again
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Network(_) => true,
+            Self::Http { status, .. } => *status == 408 || *status == 429 || *status >= 500,
+            Self::Parse(_) | Self::NotFound(_) => false,
+        }
+    }
+}
+
+impl Display for FetchError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Network(e) => write!(f, "network error: {}", e),
+            Self::Http { status, url } => write!(f, "{} responded with status {}", url, status),
+            Self::Parse(e) => write!(f, "{}", e),
+            Self::NotFound(e) => write!(f, "{} not found", e),
+        }
+    }
+}
+impl Error for FetchError {}
+
+impl From<JsValue> for FetchError {
+    fn from(value: JsValue) -> Self {
+        Self::Network(value.as_string().unwrap_or_else(|| format!("{:?}", value)))
+    }
+}
+
+/// wait for `ms` milliseconds
+pub async fn sleep(ms: i32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        if let Some(window) = web_sys::window() {
+            let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms);
+        }
+    });
+    let _ = JsFuture::from(promise).await;
+}
+
+/// the response of a request, conditional or not
+pub enum Fetched {
+    Modified {
+        body: String,
+        etag: String,
+        last_modified: String,
+    },
+    /// the cached one is still fresh
+    NotModified,
+}
+
+/// request the url, with the validators of cached response if any
+/// the response of unsuccessful status is an error
+pub async fn request(url: &str, cached: Option<&Entry>) -> Result<Fetched, FetchError> {
+    log::debug!("{}", &format!("url: {:?}", url));
+    let window = web_sys::window().ok_or_else(|| FetchError::Network("no window".into()))?;
+    let req = web_sys::Request::new_with_str(url)?;
+    if let Some(entry) = cached {
+        if !entry.etag.is_empty() {
+            req.headers().set("If-None-Match", &entry.etag)?;
+        }
+        if !entry.last_modified.is_empty() {
+            req.headers()
+                .set("If-Modified-Since", &entry.last_modified)?;
+        }
+    }
+    let res = JsFuture::from(window.fetch_with_request(&req))
+        .await?
+        .dyn_into::<web_sys::Response>()
+        .map_err(|_| FetchError::Parse(format!("{} is not a response", url)))?;
+    match res.status() {
+        200..=299 => {}
+        304 if cached.is_some() => return Ok(Fetched::NotModified),
+        404 | 410 => return Err(FetchError::NotFound(url.into())),
+        status => {
+            return Err(FetchError::Http {
+                status,
+                url: url.into(),
+            })
+        }
+    }
+    let header = |name: &str| res.headers().get(name).ok().flatten().unwrap_or_default();
+    let etag = header("ETag");
+    let last_modified = header("Last-Modified");
+    let body = JsFuture::from(res.text()?)
+        .await?
+        .as_string()
+        .ok_or_else(|| FetchError::Parse(format!("{} is not text", url)))?;
+    Ok(Fetched::Modified {
+        body,
+        etag,
+        last_modified,
+    })
+}
+
+/// fetch the text at url, the response of unsuccessful status is an error
+pub async fn fetch_text(url: &str) -> Result<String, FetchError> {
+    match request(url, None).await? {
+        Fetched::Modified { body, .. } => Ok(body),
+        Fetched::NotModified => unreachable!("not a conditional request"),
+    }
+}
+
+/// request the url, the transient failures are retried
+/// with exponential backoff
+async fn request_with_retry(url: &str, cached: Option<&Entry>) -> Result<Fetched, FetchError> {
+    let mut delay = constant::FETCH_BACKOFF_MS;
+    let mut attempt = 0;
+    loop {
+        match request(url, cached).await {
+            Err(e) if e.is_transient() && attempt < constant::FETCH_RETRIES => {
+                log::warn!("fetching {} failed: {}, retry in {}ms", url, e, delay);
+                sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            res => return res,
+        }
+    }
+}
+
+/// fetch the text at url, the transient failures are retried
+/// with exponential backoff
+pub async fn fetch_with_retry(url: &str) -> Result<String, FetchError> {
+    match request_with_retry(url, None).await? {
+        Fetched::Modified { body, .. } => Ok(body),
+        Fetched::NotModified => unreachable!("not a conditional request"),
+    }
+}
+
+/// fetch the text at url through the persistent cache,
+/// the cached one is revalidated and used if not modified,
+/// or the site is not reachable, eg: offline
+pub async fn fetch_cached(url: &str) -> Result<String, FetchError> {
+    let cached = cache::get(url);
+    match request_with_retry(url, cached.as_ref()).await {
+        Ok(Fetched::NotModified) => {
+            log::trace!("{} not modified", url);
+            Ok(cached.unwrap().body)
+        }
+        Ok(Fetched::Modified {
+            body,
+            etag,
+            last_modified,
+        }) => {
+            if cached.as_ref().map(|e| e.body != body).unwrap_or(true) {
+                cache::put(url, &Entry::new(body.clone(), etag, last_modified));
+            }
+            Ok(body)
+        }
+        Err(FetchError::NotFound(e)) => {
+            cache::remove(url);
+            Err(FetchError::NotFound(e))
+        }
+        Err(e) if e.is_transient() && cached.is_some() => {
+            log::warn!("{} is served from cache: {}", url, e);
+            Ok(cached.unwrap().body)
+        }
+        Err(e) => Err(e),
+    }
+}
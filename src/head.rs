@@ -0,0 +1,140 @@
+//! Here provide the managing of document head, the title and the metadata
+//! of the routed page are set once it is rendered, see `common::seo`
+use crate::common::seo::{Head, Person, Site};
+use crate::config::{self, SiteConfig};
+use crate::content::{slugify, Blog};
+use std::cell::RefCell;
+use web_sys::{Document, Element};
+
+/// the class of elements added into head, they are replaced
+/// once another page is routed to
+const CLASS: &str = "noer-head";
+
+thread_local! {
+    /// the head set last time, it is not set again if unchanged
+    static CURRENT: RefCell<Head> = RefCell::new(Head::default());
+}
+
+/// the absolute url of the site, `url` in config or where it is served
+pub fn site_url(config: &SiteConfig) -> String {
+    if config.url.is_empty() {
+        config::site_url("")
+    } else {
+        config.url.clone()
+    }
+}
+
+/// the absolute url of route as the app routes it, eg: "posts/123/title"
+pub fn route_url(config: &SiteConfig, route: &str) -> String {
+    config.routing().url(&site_url(config), route)
+}
+
+fn site<'a>(config: &'a SiteConfig, url: &'a str) -> Site<'a> {
+    Site {
+        name: &config.site_name,
+        description: &config.site_description,
+        url,
+        logo: &config.logo_pic,
+        config: &config.seo,
+    }
+}
+
+/// set the head of a page, an empty title is the site itself
+pub fn set_page(config: &SiteConfig, title: &str, description: &str, route: &str) {
+    let url = site_url(config);
+    set(&Head::page(
+        &site(config, &url),
+        title,
+        description,
+        &route_url(config, route),
+    ));
+}
+
+/// set the head of the page not found, it is not indexed
+pub fn set_not_found(config: &SiteConfig) {
+    let url = site_url(config);
+    let mut head = Head::page(&site(config, &url), config.t("page_not_found"), "", "");
+    head.meta.push(("name", "robots".into(), "noindex".into()));
+    set(&head);
+}
+
+/// set the head of post
+pub fn set_post(config: &SiteConfig, blog: &Blog) {
+    let url = site_url(config);
+    let authors = blog
+        .authors_or(&config.admin)
+        .into_iter()
+        .map(|name| Person {
+            url: route_url(config, &format!("authors/{}", slugify(&name))),
+            name,
+        })
+        .collect::<Vec<_>>();
+    let route = format!("posts/{}/{}", blog.meta.id, blog.meta.title);
+    set(&Head::post(
+        &site(config, &url),
+        blog,
+        &authors,
+        &route_url(config, &route),
+    ));
+}
+
+/// create the element with the attributes in class `CLASS`
+fn element(document: &Document, tag: &str, attrs: &[(&str, &str)]) -> Option<Element> {
+    let e = document
+        .create_element(tag)
+        .map_err(|e| log::error!("<{}> not created: {:?}", tag, e))
+        .ok()?;
+    for (name, value) in attrs.iter().chain([("class", CLASS)].iter()) {
+        if let Err(err) = e.set_attribute(name, value) {
+            log::error!("{} of <{}> not set: {:?}", name, tag, err);
+        }
+    }
+    Some(e)
+}
+
+/// replace the title and the elements added last time with the head
+pub fn set(head: &Head) {
+    if CURRENT.with(|e| *e.borrow() == *head) {
+        return;
+    }
+    let document = match web_sys::window().and_then(|w| w.document()) {
+        Some(document) => document,
+        None => return,
+    };
+    let root = match document.query_selector("head").ok().flatten() {
+        Some(root) => root,
+        None => return,
+    };
+    document.set_title(&head.title);
+    let old = document.get_elements_by_class_name(CLASS);
+    // the collection is live, the removed ones are gone from it
+    while let Some(e) = old.item(0) {
+        e.remove();
+    }
+    let mut elements = head
+        .meta
+        .iter()
+        .filter_map(|(attr, key, content)| {
+            element(&document, "meta", &[(attr, key), ("content", content)])
+        })
+        .collect::<Vec<_>>();
+    if !head.canonical.is_empty() {
+        elements.extend(element(
+            &document,
+            "link",
+            &[("rel", "canonical"), ("href", &head.canonical)],
+        ));
+    }
+    if !head.json_ld.is_empty() {
+        if let Some(script) = element(&document, "script", &[("type", "application/ld+json")]) {
+            script.set_text_content(Some(&head.json_ld));
+            elements.push(script);
+        }
+    }
+    for e in elements {
+        if let Err(err) = root.append_child(&e) {
+            log::error!("head not updated: {:?}", err);
+        }
+    }
+    CURRENT.with(|e| *e.borrow_mut() = head.clone());
+}
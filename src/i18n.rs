@@ -0,0 +1,178 @@
+//! Here provide the message catalog of UI strings
+//! each language is a list of key-message pairs,
+//! the english one is used for the missing language or key
+
+/// the language used if nothing matched
+pub const DEFAULT_LANG: &str = "en";
+
+/// the texts of a language, (key, text)
+type Catalog = &'static [(&'static str, &'static str)];
+
+const EN: Catalog = &[
+    ("blogs_not_fetched", "Blogs Not Fetched"),
+    ("blog_not_loaded", "Blog Not Loaded Due To:"),
+    ("unexpected_error", "Unexpected Error"),
+    ("load_more_page", "Load More Page"),
+    ("list_view", "List View"),
+    ("grid_view", "Grid View"),
+    ("no_page_found", "No Page Found"),
+    ("page_not_found", "Page not found"),
+    ("page_not_exist", "Page page does not seem to exist"),
+    ("site_config_problems", "Site Configuration Problems:"),
+    ("by", "by "),
+    ("and", " and "),
+    ("no_posts_of_author", "No posts of the author"),
+    ("no_posts_in_language", "No posts in the language"),
+    ("translations", "Read in:"),
+    ("comments", "Comments"),
+    ("no_comments", "No comments yet"),
+    ("comments_not_loaded", "Comments not loaded"),
+    ("retry", "Retry"),
+    ("index_not_loaded", "Blogs Index Not Loaded Due To:"),
+    ("did_you_mean", "Were you looking for:"),
+    ("search_posts", "Search the posts"),
+    ("no_matches", "No posts matched"),
+    ("contents", "Contents"),
+    ("copy", "Copy"),
+    ("copied", "Copied"),
+    ("copy_failed", "Copy failed"),
+    ("close", "Close"),
+    ("previous_image", "Previous image"),
+    ("next_image", "Next image"),
+];
+
+const ZH: Catalog = &[
+    ("blogs_not_fetched", "博客未获取"),
+    ("blog_not_loaded", "博客加载失败，原因："),
+    ("unexpected_error", "未知错误"),
+    ("load_more_page", "加载更多"),
+    ("list_view", "列表视图"),
+    ("grid_view", "网格视图"),
+    ("no_page_found", "没有页面"),
+    ("page_not_found", "页面不存在"),
+    ("page_not_exist", "您访问的页面似乎不存在"),
+    ("site_config_problems", "站点配置问题："),
+    ("by", "作者："),
+    ("and", "、"),
+    ("no_posts_of_author", "该作者还没有文章"),
+    ("no_posts_in_language", "该语言还没有文章"),
+    ("translations", "其他语言："),
+    ("comments", "评论"),
+    ("no_comments", "还没有评论"),
+    ("comments_not_loaded", "评论加载失败"),
+    ("retry", "重试"),
+    ("index_not_loaded", "博客索引加载失败，原因："),
+    ("did_you_mean", "您是否在找："),
+    ("search_posts", "搜索文章"),
+    ("no_matches", "没有匹配的文章"),
+    ("contents", "目录"),
+    ("copy", "复制"),
+    ("copied", "已复制"),
+    ("copy_failed", "复制失败"),
+    ("close", "关闭"),
+    ("previous_image", "上一张"),
+    ("next_image", "下一张"),
+];
+
+const FR: Catalog = &[
+    ("blogs_not_fetched", "Articles non récupérés"),
+    ("blog_not_loaded", "Article non chargé :"),
+    ("unexpected_error", "Erreur inattendue"),
+    ("load_more_page", "Charger plus"),
+    ("list_view", "Vue liste"),
+    ("grid_view", "Vue grille"),
+    ("no_page_found", "Aucune page"),
+    ("page_not_found", "Page introuvable"),
+    ("page_not_exist", "Cette page ne semble pas exister"),
+    (
+        "site_config_problems",
+        "Problèmes de configuration du site :",
+    ),
+    ("by", "par "),
+    ("and", " et "),
+    ("no_posts_of_author", "Aucun article de cet auteur"),
+    ("no_posts_in_language", "Aucun article dans cette langue"),
+    ("translations", "Lire en :"),
+    ("comments", "Commentaires"),
+    ("no_comments", "Aucun commentaire"),
+    ("comments_not_loaded", "Commentaires non chargés"),
+    ("retry", "Réessayer"),
+    ("index_not_loaded", "Index des articles non chargé :"),
+    ("did_you_mean", "Cherchiez-vous :"),
+    ("search_posts", "Rechercher les articles"),
+    ("no_matches", "Aucun article ne correspond"),
+    ("contents", "Sommaire"),
+    ("copy", "Copier"),
+    ("copied", "Copié"),
+    ("copy_failed", "Échec de la copie"),
+    ("close", "Fermer"),
+    ("previous_image", "Image précédente"),
+    ("next_image", "Image suivante"),
+];
+
+/// all languages with catalog, (code, name, catalog)
+const LANGS: &[(&str, &str, Catalog)] = &[
+    ("en", "English", EN),
+    ("zh", "中文", ZH),
+    ("fr", "Français", FR),
+];
+
+/// the primary subtag of language tag
+/// eg: "zh-CN" -> "zh", "en_US" -> "en"
+pub fn primary(lang: &str) -> String {
+    lang.split(['-', '_'])
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_lowercase()
+}
+
+/// whether there is catalog for the language
+pub fn is_supported(lang: &str) -> bool {
+    let code = primary(lang);
+    LANGS.iter().any(|(e, _, _)| *e == code)
+}
+
+/// translate the key into message of the language
+pub fn t(lang: &str, key: &str) -> &'static str {
+    let code = primary(lang);
+    let lookup = |catalog: &'static [(&'static str, &'static str)]| {
+        catalog.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+    };
+    LANGS
+        .iter()
+        .find(|(e, _, _)| *e == code)
+        .and_then(|(_, _, catalog)| lookup(catalog))
+        .or_else(|| lookup(EN))
+        .unwrap_or("")
+}
+
+/// name of the language to display, the code itself if unknown
+#[cfg(target_arch = "wasm32")]
+pub fn language_name(lang: &str) -> String {
+    let code = primary(lang);
+    LANGS
+        .iter()
+        .find(|(e, _, _)| *e == code)
+        .map(|(_, name, _)| name.to_string())
+        .unwrap_or_else(|| lang.to_string())
+}
+
+/// the preferred language of the browser
+#[cfg(target_arch = "wasm32")]
+pub fn navigator_language() -> Option<String> {
+    web_sys::window().and_then(|w| w.navigator().language())
+}
+
+/// set `lang` of the `<html>` element
+#[cfg(target_arch = "wasm32")]
+pub fn set_document_lang(lang: &str) {
+    if let Some(root) = web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.document_element())
+    {
+        if let Err(e) = root.set_attribute("lang", lang) {
+            log::error!("lang of document not set: {:?}", e);
+        }
+    }
+}
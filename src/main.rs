@@ -1,24 +1,71 @@
+// the modules of the app are built for the browser only,
+// the command line is built for the native target
+#[cfg(target_arch = "wasm32")]
+mod analytics;
+#[cfg(target_arch = "wasm32")]
+mod cache;
+#[cfg(not(target_arch = "wasm32"))]
+mod cli;
+#[cfg(target_arch = "wasm32")]
+mod comments;
+mod common;
+#[cfg(target_arch = "wasm32")]
 mod components;
+mod config;
 mod constant;
+#[cfg(target_arch = "wasm32")]
 mod content;
+#[cfg(target_arch = "wasm32")]
+mod fetch;
+#[cfg(target_arch = "wasm32")]
+mod head;
+mod i18n;
+#[cfg(target_arch = "wasm32")]
+mod markdown;
+#[cfg(target_arch = "wasm32")]
 mod pages;
+#[cfg(target_arch = "wasm32")]
 mod parser;
+#[cfg(target_arch = "wasm32")]
+mod routing;
+#[cfg(target_arch = "wasm32")]
+mod scroll;
 
-use crate::content::Blog;
-use crate::content::BlogMeta;
+#[cfg(target_arch = "wasm32")]
+use crate::common::url::Routing;
+#[cfg(target_arch = "wasm32")]
+use crate::config::SiteConfig;
+#[cfg(target_arch = "wasm32")]
+use crate::content::{Blog, BlogMeta};
+#[cfg(target_arch = "wasm32")]
+use crate::fetch::FetchError;
+#[cfg(target_arch = "wasm32")]
 use crate::pages::post::FetchState;
-use crate::parser::read_dir;
-use parser::Parser;
-use yew::prelude::*;
+#[cfg(target_arch = "wasm32")]
+use crate::parser::{load_authors, read_dir, ParseAct, Parser};
+#[cfg(target_arch = "wasm32")]
+use components::social_links::SocialLinks;
+#[cfg(target_arch = "wasm32")]
+use pages::{
+    author::AuthorPosts, lang::LangPosts, page_not_found::PageNotFound, post::Post,
+    post_list::PostList,
+};
+#[cfg(target_arch = "wasm32")]
+use std::rc::Rc;
+#[cfg(target_arch = "wasm32")]
+use yew::{html::Scope, prelude::*};
+#[cfg(target_arch = "wasm32")]
 use yew_router::prelude::*;
 
-use pages::{page_not_found::PageNotFound, post::Post, post_list::PostList};
-use yew::html::Scope;
-
+#[cfg(target_arch = "wasm32")]
 #[derive(Routable, PartialEq, Clone, Debug)]
 pub enum Route {
     #[at("/posts/:id/:title")]
     Post { id: u64, title: String },
+    #[at("/authors/:name")]
+    Author { name: String },
+    #[at("/lang/:lang")]
+    Lang { lang: String },
     #[at("/")]
     Home,
     #[not_found]
@@ -26,9 +73,12 @@ pub enum Route {
     NotFound,
 }
 
+#[cfg(target_arch = "wasm32")]
 pub type ParseActContext = UseReducerHandle<Parser>;
-use crate::parser::ParseAct;
+#[cfg(target_arch = "wasm32")]
+pub type SiteConfigContext = Rc<SiteConfig>;
 
+#[cfg(target_arch = "wasm32")]
 #[function_component(App)]
 pub fn app() -> Html {
     let ctx = use_reducer(|| Parser::new());
@@ -40,14 +90,24 @@ pub fn app() -> Html {
     }
 }
 
+#[cfg(target_arch = "wasm32")]
 pub enum Msg {
+    LoadConfig,
+    ConfigLoaded(SiteConfig, Vec<String>),
     LoadBlogMeta,
     Ready,
+    Failed(FetchError),
+    Notified,
 }
 
+#[cfg(target_arch = "wasm32")]
 pub struct Model {
     state: FetchState<()>,
+    config: SiteConfigContext,
+    config_errors: Vec<String>,
+    notified: bool,
 }
+#[cfg(target_arch = "wasm32")]
 impl Component for Model {
     type Message = Msg;
     type Properties = ();
@@ -57,11 +117,16 @@ impl Component for Model {
         static mut INIT: Once = Once::new();
         unsafe {
             INIT.call_once(|| {
-                _ctx.link().send_message(Msg::LoadBlogMeta);
+                _ctx.link().send_message(Msg::LoadConfig);
             });
         }
+        let mut config = SiteConfig::default();
+        config.resolve_language();
         Self {
             state: FetchState::NotFetching,
+            config: Rc::new(config),
+            config_errors: Vec::new(),
+            notified: false,
         }
     }
 
@@ -75,114 +140,165 @@ impl Component for Model {
                 self.state = FetchState::Success(());
                 true
             }
-            Msg::LoadBlogMeta => {
+            Msg::Failed(err) => {
+                log::error!("markdown index not loaded: {}", err);
+                self.state = FetchState::Failed(err);
+                true
+            }
+            Msg::Notified => {
+                self.notified = true;
+                true
+            }
+            Msg::LoadConfig => {
                 _ctx.link().send_future(async move {
-                    let paths = read_dir().await;
-                    log::trace!("markdown index: {:?}", paths);
-                    parser.dispatch(ParseAct::BlogPath(paths));
-                    parser.dispatch(ParseAct::MoreBlogMeta);
-                    Msg::Ready
+                    let (config, errors) = config::load_config().await;
+                    Msg::ConfigLoaded(config, errors)
                 });
                 false
             }
+            Msg::ConfigLoaded(mut config, errors) => {
+                config.apply_log_level();
+                config.resolve_language();
+                i18n::set_document_lang(&config.language);
+                analytics::init(&config.analytics);
+                routing::restore_redirect(config.routing());
+                scroll::init(config.routing());
+                errors
+                    .iter()
+                    .for_each(|e| log::error!("site config: {}", e));
+                parser.dispatch(ParseAct::ItemsPerPage(config.items_per_page));
+                self.config = Rc::new(config);
+                self.config_errors = errors;
+                _ctx.link().send_message(Msg::LoadBlogMeta);
+                true
+            }
+            Msg::LoadBlogMeta => {
+                _ctx.link().send_future(async move {
+                    match read_dir().await {
+                        Ok(paths) => {
+                            log::trace!("markdown index: {:?}", paths);
+                            parser.dispatch(ParseAct::BlogPath(paths));
+                            parser.dispatch(ParseAct::MoreBlogMeta);
+                            // the bylines of posts are shown without fetching them
+                            match load_authors().await {
+                                Ok(authors) => parser.dispatch(ParseAct::Authors(authors)),
+                                Err(e) => log::debug!("manifest not loaded: {}", e),
+                            }
+                            Msg::Ready
+                        }
+                        Err(err) => Msg::Failed(err),
+                    }
+                });
+                self.state = FetchState::Fetching;
+                true
+            }
         }
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
-        let dom_parser = web_sys::DomParser::new().unwrap();
-        let mut footer = Vec::new();
-        let input_str = format!("<div>{}</div>", constant::SITE_DESCRIPTION);
-        if let Ok(element) =
-            dom_parser.parse_from_string(&input_str, web_sys::SupportedType::TextHtml)
-        {
-            let eles = element.body().unwrap().children();
-            for ind in 0..eles.length() {
-                let node = eles.get_with_index(ind).unwrap();
-                let vnode = Html::VRef(node.into());
-                footer.push(vnode);
-            }
-        }
-        match self.state {
-            FetchState::Success(_) => {
-                html! {
-                    <BrowserRouter>
-                        { self.view_nav(ctx.link()) }
-
-                    <main>
-                        <Switch<Route> render={Switch::render(switch)} />
-                    </main>
-                        <footer class="footer">
-                            <div class="content has-text-centered">
-                              {
-                                  html!{ for footer }
-                              }
-                            </div>
-                        </footer>
-                    </BrowserRouter>
-                }
-            }
+        let input_str = format!(
+            "<div>{}</div>",
+            markdown::sanitize(&self.config.site_description, &self.config.sanitize)
+        );
+        let footer = markdown::to_vnodes(&input_str).unwrap_or_default();
+        let content = match self.state {
+            FetchState::Success(_) => self.view_router(html! {
+                <>
+                    { self.view_nav(ctx.link()) }
+                    { self.view_config_errors(ctx.link()) }
+
+                <main>
+                    <Switch<Route> render={Switch::render(switch)} />
+                </main>
+                    <footer class="footer">
+                        <div class="content has-text-centered">
+                          {
+                              html!{ for footer }
+                          }
+                          <p class="footer-links">
+                              <SocialLinks links={self.config.links.clone()} />
+                          </p>
+                        </div>
+                    </footer>
+                </>
+            }),
+            FetchState::Failed(ref err) => html! {
+                <>
+                { self.view_nav(ctx.link()) }
+                { self.view_index_error(ctx.link(), err) }
+                </>
+            },
             _ => html! {
                 <>
                 { self.view_nav(ctx.link()) }
                 </>
             },
+        };
+        html! {
+            <ContextProvider<SiteConfigContext> context={self.config.clone()}>
+                { content }
+            </ContextProvider<SiteConfigContext>>
         }
     }
 }
+#[cfg(target_arch = "wasm32")]
 impl Model {
+    /// the router of the routing mode in site config
+    fn view_router(&self, children: Html) -> Html {
+        match self.config.routing() {
+            Routing::History => html! { <BrowserRouter>{ children }</BrowserRouter> },
+            Routing::Hash => html! { <HashRouter>{ children }</HashRouter> },
+        }
+    }
+
+    fn view_config_errors(&self, link: &Scope<Self>) -> Html {
+        if self.config_errors.is_empty() || self.notified {
+            return html! {};
+        }
+        let onclick = link.callback(|_| Msg::Notified);
+        let errors = self.config_errors.iter().map(|e| html! { <li>{ e }</li> });
+        html! {
+            <div class="container mt-24 notification is-warning">
+                <button class="delete" {onclick}> </button>
+                <strong class="is-large">{ self.config.t("site_config_problems") }</strong>
+                <ul>
+                    { for errors }
+                </ul>
+            </div>
+        }
+    }
+
+    /// the index of posts is not loaded, nothing can be shown but retry
+    fn view_index_error(&self, link: &Scope<Self>, err: &FetchError) -> Html {
+        let onclick = link.callback(|_| Msg::LoadBlogMeta);
+        html! {
+            <div class="container mt-24 notification is-danger">
+                <strong class="is-large">{ self.config.t("index_not_loaded") }</strong>
+                <br />
+                <p class="center">{ err.to_string() }</p>
+                <button class="button is-light mt-3" {onclick}>{ self.config.t("retry") }</button>
+            </div>
+        }
+    }
+
     fn view_user_info(&self) -> Html {
-        let s = constant::USER_INFO;
-        let mut infos = Vec::new();
-        s.split("\n")
-            .map(|e| e.trim())
-            .filter(|e| e.len() != 0)
-            .for_each(|e| {
-                let pairs: Vec<&str> = e
-                    .splitn(2, ":")
-                    .map(|e| e.trim().trim_end_matches(","))
-                    .collect();
-                if pairs.len() == 2 {
-                    log::debug!("key: {}, value: {}", pairs[0], pairs[1]);
-                    let item = if pairs[0] == "email" {
-                        html! {
-                            <a class="navbar-item" href={format!("mailto:{}", pairs[1])} >
-                                { pairs[0] }
-                            </a>
-                        }
-                    } else {
-                        html! {
-                            <a class="navbar-item" target="_blank" href={pairs[1]} >
-                                { pairs[0] }
-                            </a>
-                        }
-                    };
-                    infos.push(item);
-                }
-            });
         html! {
             <div class="navbar-dropdown">
-              { for infos }
+                <SocialLinks links={self.config.links.clone()} class="navbar-item" />
             </div>
         }
     }
 
     fn view_nav(&self, _link: &Scope<Self>) -> Html {
-        let href = format!("/{}/", constant::SUBPATH.replace("/", ""));
-        let logo = format!(
-            "/{}/{}",
-            constant::SUBPATH.replace("/", ""),
-            constant::LOGO_PIC
-        );
-        let avatar = format!(
-            "/{}/{}",
-            constant::SUBPATH.replace("/", ""),
-            constant::AVATR_PIC
-        );
+        let config = &self.config;
+        let href = config.href("");
+        let logo = config.href(&config.logo_pic);
+        let avatar = config.href(&config.avatar_pic);
         html! {
             <nav class="navbar" role="navigation" aria-label="main navigation">
                 <div class="navbar-brand">
                     <a class="navbar-item" href={href.clone()}>
-                    <h1 class="navbar-item is-size-3">{ constant::SITE_NAME }</h1>
+                    <h1 class="navbar-item is-size-3">{ &config.site_name }</h1>
                     </a>
                     <a class="navbar-item" href={href.clone()}>
                         <figure class="image is-rounded pr-3">
@@ -200,7 +316,7 @@ impl Model {
                         </a>
                         <div class="navbar-item has-dropdown is-hoverable">
                         <a class="title is-5" href={href}>
-                            { constant::ADMIN }
+                            { &config.admin }
                         </a>
                           { self.view_user_info() }
                         </div>
@@ -212,11 +328,20 @@ impl Model {
     }
 }
 
+#[cfg(target_arch = "wasm32")]
 fn switch(routes: &Route) -> Html {
+    analytics::page_view(&routes.to_path());
+    scroll::entered();
     match routes.clone() {
         Route::Post { id, title } => {
             html! { <Post id={id} title={title} /> }
         }
+        Route::Author { name } => {
+            html! { <AuthorPosts name={name} /> }
+        }
+        Route::Lang { lang } => {
+            html! { <LangPosts lang={lang} /> }
+        }
         Route::Home => {
             html! { <PostList /> }
         }
@@ -226,13 +351,17 @@ fn switch(routes: &Route) -> Html {
     }
 }
 
+#[cfg(target_arch = "wasm32")]
 fn main() {
-    // set logger depends on the logger filter
-    match constant::MODE {
-        constant::Modes::Release => {}
-        constant::Modes::Development(level) => {
-            wasm_logger::init(wasm_logger::Config::new(level));
-        }
-    }
+    // the logger is always set, its level is decided by
+    // the mode in site config once loaded
+    wasm_logger::init(wasm_logger::Config::new(log::Level::Trace));
+    SiteConfig::default().apply_log_level();
     yew::start_app::<App>();
 }
+
+/// the command line to manage the site, eg: `cargo run -- new "My Title"`
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    cli::run();
+}
@@ -0,0 +1,66 @@
+//! Here provide the pipeline that renders markdown into html
+//! posts and comments are rendered by the same pipeline,
+//! the html string is rendered in `common::markdown`
+pub use crate::common::markdown::{options, to_html};
+pub use crate::common::sanitize::sanitize;
+use web_sys::Element;
+use yew::Html;
+
+/// the class of the buttons copying code, see `add_copy_buttons`
+pub const COPY_BUTTON: &str = "code-copy";
+
+/// parse the html string into nodes that can be inserted into DOM
+/// None if it is not parsed
+pub fn to_vnodes(html: &str) -> Option<Vec<Html>> {
+    to_vnodes_with(html, |_| {})
+}
+
+/// same as `to_vnodes`, the body parsed is changed by `f` before,
+/// eg: the buttons are added
+pub fn to_vnodes_with<F>(html: &str, f: F) -> Option<Vec<Html>>
+where
+    F: FnOnce(&Element),
+{
+    let dom_parser = web_sys::DomParser::new().ok()?;
+    let element = dom_parser
+        .parse_from_string(html, web_sys::SupportedType::TextHtml)
+        .ok()?;
+    let body = element.body()?;
+    f(&body);
+    let eles = body.children();
+    let mut nodes = Vec::new();
+    for ind in 0..eles.length() {
+        let node = eles.get_with_index(ind).unwrap();
+        nodes.push(Html::VRef(node.into()));
+    }
+    Some(nodes)
+}
+
+/// add a button to each code block that copies its code, the clicks
+/// are handled by the component, see `COPY_BUTTON`
+pub fn add_copy_buttons(body: &Element, label: &str) {
+    let document = match body.owner_document() {
+        Some(document) => document,
+        None => return,
+    };
+    let blocks = body.get_elements_by_class_name("code-block");
+    for i in 0..blocks.length() {
+        let block = match blocks.item(i) {
+            Some(block) => block,
+            None => continue,
+        };
+        let button = match document.create_element("button") {
+            Ok(button) => button,
+            Err(e) => {
+                log::error!("copy button not created: {:?}", e);
+                return;
+            }
+        };
+        let _ = button.set_attribute("type", "button");
+        let _ = button.set_attribute("class", &format!("button is-small {}", COPY_BUTTON));
+        button.set_text_content(Some(label));
+        if let Err(e) = block.prepend_with_node_1(&button) {
+            log::error!("copy button not added: {:?}", e);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::components::author_box::AuthorBox;
+use crate::components::post_card::BlogCard;
+use crate::config::SiteConfig;
+use crate::content::{slugify, Blog, BlogMeta};
+use crate::head;
+use crate::parser::fetch_where;
+use crate::scroll;
+use crate::ParseActContext;
+use crate::SiteConfigContext;
+use yew::prelude::*;
+
+#[derive(Clone, Debug, Eq, PartialEq, Properties)]
+pub struct Props {
+    /// the slug of author name
+    pub name: String,
+}
+
+pub enum Msg {
+    Loaded,
+}
+
+/// the name of author as written in front matter, `slug` is the one in path
+fn author_name(config: &SiteConfig, blogs: &[&Blog], slug: &str) -> String {
+    blogs
+        .first()
+        .and_then(|blog| {
+            blog.authors_or(&config.admin)
+                .into_iter()
+                .find(|e| slugify(e) == slug)
+        })
+        .unwrap_or_else(|| config.author(slug).name)
+}
+
+/// the page lists all posts of an author
+pub struct AuthorPosts {
+    /// number of posts not fetched yet
+    pending: usize,
+}
+
+impl Component for AuthorPosts {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let (parser, _) = ctx
+            .link()
+            .context::<ParseActContext>(Callback::noop())
+            .expect("Parser Context not found");
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        // the posts of the author in the manifest are fetched,
+        // and the ones it does not know are fetched to find out
+        let slug = ctx.props().name.clone();
+        let keep = |meta: &BlogMeta| match parser.authors_of(meta, &config.admin) {
+            Some(authors) => authors.iter().any(|e| slugify(e) == slug),
+            None => true,
+        };
+        let pending = fetch_where(ctx.link(), &parser, keep, || Msg::Loaded);
+        Self { pending }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::Loaded => {
+                self.pending = self.pending.saturating_sub(1);
+                true
+            }
+        }
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        let (parser, _) = ctx
+            .link()
+            .context::<ParseActContext>(Callback::noop())
+            .expect("Parser Context not found");
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        let name = &ctx.props().name;
+        let author = author_name(&config, &parser.blogs_by(name, &config.admin), name);
+        let bio = config.author(&author).bio;
+        head::set_page(&config, &author, &bio, &format!("authors/{}", name));
+        scroll::settle();
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let (parser, _) = ctx
+            .link()
+            .context::<ParseActContext>(Callback::noop())
+            .expect("Parser Context not found");
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        let name = &ctx.props().name;
+        let blogs = parser.blogs_by(name, &config.admin);
+        let author = author_name(&config, &blogs, name);
+        let cards = blogs.iter().map(|blog| {
+            html! {
+                <li class="list-item mb-1">
+                    <BlogCard id={blog.meta.id} title={blog.meta.title.clone()} display="listTile" />
+                </li>
+            }
+        });
+        html! {
+            <div class="section container">
+                <AuthorBox name={author} />
+                {
+                    if self.pending > 0 {
+                        html! { <progress class="progress is-small is-info" max="100"></progress> }
+                    } else if blogs.is_empty() {
+                        html! { <p>{ config.t("no_posts_of_author") }</p> }
+                    } else {
+                        html! {}
+                    }
+                }
+                <div class="column">
+                    <ul class="list">
+                        { for cards }
+                    </ul>
+                </div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use crate::components::post_card::BlogCard;
+use crate::head;
+use crate::i18n;
+use crate::parser::fetch_all;
+use crate::scroll;
+use crate::ParseActContext;
+use crate::SiteConfigContext;
+use yew::prelude::*;
+
+#[derive(Clone, Debug, Eq, PartialEq, Properties)]
+pub struct Props {
+    pub lang: String,
+}
+
+pub enum Msg {
+    Loaded,
+}
+
+/// the page lists all posts in a language
+pub struct LangPosts {
+    /// number of posts not fetched yet
+    pending: usize,
+}
+
+impl Component for LangPosts {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let (parser, _) = ctx
+            .link()
+            .context::<ParseActContext>(Callback::noop())
+            .expect("Parser Context not found");
+        // language is known only in front matter,
+        // so all posts are fetched to find them
+        let pending = fetch_all(ctx.link(), &parser, || Msg::Loaded);
+        Self { pending }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::Loaded => {
+                self.pending = self.pending.saturating_sub(1);
+                true
+            }
+        }
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        let lang = &ctx.props().lang;
+        head::set_page(
+            &config,
+            &i18n::language_name(lang),
+            "",
+            &format!("lang/{}", lang),
+        );
+        scroll::settle();
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let (parser, _) = ctx
+            .link()
+            .context::<ParseActContext>(Callback::noop())
+            .expect("Parser Context not found");
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        let lang = &ctx.props().lang;
+        let blogs = parser.blogs_in(lang, &config.language);
+        let cards = blogs.iter().map(|blog| {
+            html! {
+                <li class="list-item mb-1">
+                    <BlogCard id={blog.meta.id} title={blog.meta.title.clone()} display="listTile" />
+                </li>
+            }
+        });
+        html! {
+            <div class="section container">
+                <h1 class="title">{ i18n::language_name(lang) }</h1>
+                {
+                    if self.pending > 0 {
+                        html! { <progress class="progress is-small is-info" max="100"></progress> }
+                    } else if blogs.is_empty() {
+                        html! { <p>{ config.t("no_posts_in_language") }</p> }
+                    } else {
+                        html! {}
+                    }
+                }
+                <div class="column">
+                    <ul class="list">
+                        { for cards }
+                    </ul>
+                </div>
+            </div>
+        }
+    }
+}
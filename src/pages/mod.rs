@@ -1,4 +1,6 @@
 //pub mod home;
+pub mod author;
+pub mod lang;
 pub mod page_not_found;
 pub mod post;
 pub mod post_list;
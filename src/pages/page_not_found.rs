@@ -1,29 +1,261 @@
+use crate::common::fuzzy;
+use crate::components::post_card::BlogCard;
+use crate::config;
+use crate::constant;
+use crate::fetch::{fetch_cached, FetchError};
+use crate::head;
+use crate::markdown;
+use crate::parser::{fetch_all, ParseAct};
+use crate::routing;
+use crate::scroll;
+use crate::ParseActContext;
+use crate::Route;
+use crate::SiteConfigContext;
+use web_sys::HtmlInputElement;
 use yew::prelude::*;
+use yew_router::prelude::*;
 
-pub struct PageNotFound;
+pub enum Msg {
+    /// the route found in the redirect table,
+    /// `Err` if there is no table
+    Redirect(Result<Option<Route>, FetchError>),
+    Loaded,
+    /// the custom content in markdown
+    Custom(String),
+    Search(String),
+}
+
+/// the path may be an alias of post, it is redirected to the post
+/// before "Page not found" is shown along with the posts
+/// whose titles are close to the path
+pub struct PageNotFound {
+    /// whether the path is looked up in the redirect table
+    looked_up: bool,
+    /// number of posts not fetched yet to find the alias
+    pending: usize,
+    /// the words to search for, from the path or the search box
+    query: String,
+    custom: Option<String>,
+}
+
+fn current_path(ctx: &Context<PageNotFound>) -> String {
+    ctx.link()
+        .location()
+        .map(|e| e.path().to_string())
+        .unwrap_or_default()
+}
+
+/// the words of the path to search for
+/// eg: "/noer/posts/123/old-title" -> "old title"
+fn query_of(path: &str) -> String {
+    let path = routing::normalize_route(path);
+    let path = path.strip_prefix("posts/").unwrap_or(&path);
+    fuzzy::words(path).join(" ")
+}
 
 impl Component for PageNotFound {
-    type Message = ();
+    type Message = Msg;
     type Properties = ();
 
-    fn create(_ctx: &Context<Self>) -> Self {
-        Self
+    fn create(ctx: &Context<Self>) -> Self {
+        let path = current_path(ctx);
+        ctx.link().send_future(async move {
+            let redirects = routing::load_redirects().await;
+            Msg::Redirect(redirects.map(|e| routing::redirect_of(&e, &path)))
+        });
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        let url = config::site_url(&config.not_found_page);
+        ctx.link().send_future_batch(async move {
+            match fetch_cached(&url).await {
+                // not the page served in place of missing file
+                Ok(md) if !md.trim_start().starts_with('<') => vec![Msg::Custom(md)],
+                _ => vec![],
+            }
+        });
+        Self {
+            looked_up: false,
+            pending: 0,
+            query: query_of(&current_path(ctx)),
+            custom: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::Redirect(Ok(Some(route))) => {
+                redirect(ctx, route);
+                false
+            }
+            Msg::Redirect(Ok(None)) => {
+                // the suggestions are among all posts, not only the loaded ones
+                let (parser, _) = ctx
+                    .link()
+                    .context::<ParseActContext>(Callback::noop())
+                    .expect("Parser Context not found");
+                parser.dispatch(ParseAct::AllBlogMeta);
+                self.looked_up = true;
+                true
+            }
+            Msg::Redirect(Err(e)) => {
+                // the table is not generated, eg: served by `trunk serve`
+                // so all posts are fetched to find the aliases
+                log::debug!("redirect table not loaded: {}", e);
+                let (parser, _) = ctx
+                    .link()
+                    .context::<ParseActContext>(Callback::noop())
+                    .expect("Parser Context not found");
+                self.pending = fetch_all(ctx.link(), &parser, || Msg::Loaded);
+                self.looked_up = true;
+                true
+            }
+            Msg::Loaded => {
+                self.pending = self.pending.saturating_sub(1);
+                true
+            }
+            Msg::Custom(md) => {
+                self.custom = Some(md);
+                true
+            }
+            Msg::Search(query) => {
+                self.query = query;
+                true
+            }
+        }
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        head::set_not_found(&config);
+        scroll::settle();
+        if !self.looked_up || self.pending > 0 {
+            return;
+        }
+        let (parser, _) = ctx
+            .link()
+            .context::<ParseActContext>(Callback::noop())
+            .expect("Parser Context not found");
+        if let Some(blog) = parser.find_alias(&current_path(ctx)) {
+            redirect(
+                ctx,
+                Route::Post {
+                    id: blog.meta.id,
+                    title: blog.meta.title.clone(),
+                },
+            );
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        if !self.looked_up || self.pending > 0 {
+            return html! { <progress class="progress is-small is-info" max="100"></progress> };
+        }
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        let oninput = ctx.link().callback(|e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::Search(input.value())
+        });
+        html! {
+            <>
+            { self.view_message(ctx) }
+            <div class="section container">
+                <div class="field">
+                    <p class="control">
+                        <input class="input" type="search" placeholder={config.t("search_posts")} value={self.query.clone()} {oninput} />
+                    </p>
+                </div>
+                { self.view_suggestions(ctx) }
+            </div>
+            </>
+        }
     }
+}
 
-    fn view(&self, _ctx: &Context<Self>) -> Html {
+impl PageNotFound {
+    /// the custom content if provided, the default message otherwise
+    fn view_message(&self, ctx: &Context<Self>) -> Html {
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        if let Some(md) = &self.custom {
+            let html = format!(
+                "<div class=\"content markdown-body\">{}</div>",
+                markdown::sanitize(&markdown::to_html(md), &config.sanitize)
+            );
+            if let Some(nodes) = markdown::to_vnodes(&html) {
+                return html! { <section class="section container">{ for nodes }</section> };
+            }
+        }
         html! {
-            <section class="hero is-danger is-bold is-large">
+            <section class="hero is-danger is-bold is-medium">
                 <div class="hero-body">
                     <div class="container">
                         <h1 class="title">
-                            { "Page not found" }
+                            { config.t("page_not_found") }
                         </h1>
                         <h2 class="subtitle">
-                            { "Page page does not seem to exist" }
+                            { config.t("page_not_exist") }
                         </h2>
                     </div>
                 </div>
             </section>
         }
     }
+
+    /// the posts whose titles are close to the query
+    fn view_suggestions(&self, ctx: &Context<Self>) -> Html {
+        if self.query.trim().is_empty() {
+            return html! {};
+        }
+        let (parser, _) = ctx
+            .link()
+            .context::<ParseActContext>(Callback::noop())
+            .expect("Parser Context not found");
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        let metas = parser.all_metas().into_iter().map(|meta| {
+            let title = meta.title.clone();
+            (meta, title)
+        });
+        let suggestions = fuzzy::best(&self.query, metas, constant::SUGGESTIONS);
+        if suggestions.is_empty() {
+            return html! { <p>{ config.t("no_matches") }</p> };
+        }
+        let cards = suggestions.into_iter().map(|meta| {
+            html! {
+                <li class="list-item mb-1">
+                    <BlogCard id={meta.id} title={meta.title} display="listTile" />
+                </li>
+            }
+        });
+        html! {
+            <>
+                <p class="subtitle is-6">{ config.t("did_you_mean") }</p>
+                <ul class="list">
+                    { for cards }
+                </ul>
+            </>
+        }
+    }
+}
+
+/// replace the current path with the route, so the alias
+/// is not kept in the history
+fn redirect(ctx: &Context<PageNotFound>, route: Route) {
+    log::debug!("redirect to {:?}", route);
+    match ctx.link().history() {
+        Some(history) => history.replace(route),
+        None => log::error!("history not found to redirect"),
+    }
 }
@@ -1,42 +1,32 @@
+use crate::analytics::ReadTracker;
+use crate::components::author_box::AuthorBox;
+use crate::components::byline::Byline;
+use crate::components::comments::Comments;
+use crate::components::lightbox::{Lightbox, LightboxImage};
+use crate::config::{self, SiteConfig};
+use crate::fetch::{fetch_cached, FetchError};
+use crate::head;
+use crate::i18n;
+use crate::markdown;
 use crate::parser::ParseAct;
+use crate::parser::{cached_blog, fetch_all, fetch_blog};
+use crate::scroll;
+use crate::Blog;
 use crate::ParseActContext;
-use crate::{parser::str2blog, Blog};
-use std::{
-    error::Error,
-    fmt::{self, Debug, Display, Formatter},
-};
-use wasm_bindgen::prelude::*;
-use wasm_bindgen::JsCast;
+use crate::Route;
+use crate::SiteConfigContext;
+use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
+use web_sys::Element;
 use yew::{html, prelude::*, Component, Context, Html};
+use yew_router::components::Link;
+use yew_router::Routable;
 
-/// read the file
+/// read the file under the site,
+/// the transient failures are retried and it is cached
 pub async fn read_file(path: &str) -> Result<String, FetchError> {
-    let window = web_sys::window().unwrap();
-    let protocol = window
-        .location()
-        .protocol()
-        .expect("Protocol is NULL")
-        .to_string();
-    let host = window
-        .location()
-        .host()
-        .expect("HOST is not NULL")
-        .to_string();
-    let mut url = format!("{}//{}/", protocol, host,);
-    use crate::constant;
-    if constant::SUBPATH != "/" {
-        url.push_str(constant::SUBPATH);
-    }
-    url.push_str(path);
-    log::debug!("{}", &format!("url: {:?}", url));
-    let res = wasm_bindgen_futures::JsFuture::from(window.fetch_with_str(&url)).await?;
-    assert!(res.is_instance_of::<web_sys::Response>());
-    let res_val = res.dyn_into::<web_sys::Response>()?;
-    let response_text = wasm_bindgen_futures::JsFuture::from(res_val.text()?).await?;
-    let ret = response_text.as_string().unwrap();
-
-    log::trace!("path: {:?} \n content: {:?}", url, ret);
-    return Ok(ret);
+    let ret = fetch_cached(&config::site_url(path)).await?;
+    log::trace!("path: {:?} \n content: {:?}", path, ret);
+    Ok(ret)
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Properties)]
@@ -45,24 +35,6 @@ pub struct Props {
     pub title: String,
 }
 
-/// Something wrong has occurred while fetching an external resource.
-#[derive(Debug, Clone, PartialEq)]
-pub struct FetchError {
-    pub err: JsValue,
-}
-impl Display for FetchError {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        Debug::fmt(&self.err, f)
-    }
-}
-impl Error for FetchError {}
-
-impl From<JsValue> for FetchError {
-    fn from(value: JsValue) -> Self {
-        Self { err: value }
-    }
-}
-
 pub enum FetchState<T> {
     NotFetching,
     Fetching,
@@ -74,13 +46,88 @@ pub enum FetchState<T> {
 pub enum Msg {
     SetMarkdownFetchState(FetchState<Blog>),
     GetMarkdown,
+    /// the post is shown from the persistent cache, check if it changed
+    Revalidate,
     Cached,
     Notified,
+    TranslationLoaded,
+    /// the button of code block is clicked to copy the code
+    Copy(Element),
+    /// the code of button is copied or not
+    Copied(Element, bool),
+    /// the image of content is clicked to zoom into
+    Zoom(Element),
+    Unzoom,
+}
+
+/// the class of the element holding the content of post
+const CONTENT: &str = "post-content";
+
+/// the images in the content of post, and the position of the one given
+fn content_images(image: &Element) -> (Vec<LightboxImage>, usize) {
+    let content = match image.closest(&format!(".{}", CONTENT)).ok().flatten() {
+        Some(content) => content,
+        None => return (vec![], 0),
+    };
+    let elements = content.get_elements_by_tag_name("img");
+    let mut images = Vec::new();
+    let mut index = 0;
+    for i in 0..elements.length() {
+        let e = match elements.item(i) {
+            Some(e) => e,
+            None => continue,
+        };
+        if e == *image {
+            index = images.len();
+        }
+        // the caption of figure, or the title of image
+        let caption = e
+            .parent_element()
+            .filter(|p| p.tag_name().eq_ignore_ascii_case("figure"))
+            .and_then(|p| p.get_elements_by_tag_name("figcaption").item(0))
+            .and_then(|c| c.text_content())
+            .or_else(|| e.get_attribute("title"))
+            .unwrap_or_default();
+        images.push(LightboxImage {
+            src: e.get_attribute("src").unwrap_or_default(),
+            alt: e.get_attribute("alt").unwrap_or_default(),
+            caption,
+        });
+    }
+    (images, index)
+}
+
+/// how long the button shows the code is copied in milliseconds
+const COPIED_MS: i32 = 2000;
+
+/// write the text into the clipboard, `navigator.clipboard` is looked up
+/// dynamically since it is not stable in web-sys
+async fn write_clipboard(text: &str) -> Result<(), JsValue> {
+    let navigator = web_sys::window()
+        .ok_or_else(|| JsValue::from_str("no window"))?
+        .navigator();
+    let clipboard = js_sys::Reflect::get(&navigator, &JsValue::from_str("clipboard"))?;
+    let write = js_sys::Reflect::get(&clipboard, &JsValue::from_str("writeText"))?
+        .dyn_into::<js_sys::Function>()?;
+    let promise = write
+        .call1(&clipboard, &JsValue::from_str(text))?
+        .dyn_into::<js_sys::Promise>()?;
+    wasm_bindgen_futures::JsFuture::from(promise).await?;
+    Ok(())
 }
 
 pub struct Post {
     post: FetchState<Blog>,
     notified: bool,
+    /// whether all blogs are requested to find the translations
+    translations_requested: bool,
+    /// the reading of the post shown, it is emitted once dropped
+    reading: Option<ReadTracker>,
+    /// the images of content and the one zoomed into
+    zoomed: Option<(Vec<LightboxImage>, usize)>,
+    /// the nodes of content, they are built once the post is fetched,
+    /// so the other updates, eg: zooming, do not parse it again
+    content: Html,
 }
 impl Component for Post {
     type Message = Msg;
@@ -95,26 +142,30 @@ impl Component for Post {
         let item = parser.inner().blogs.get(&id);
         if item.is_some() {
             log::debug!("Cached: {}", ctx.props().title);
-            return Self {
-                post: FetchState::Success(item.unwrap().clone()),
-                notified: false,
-            };
+            return Self::new(ctx, FetchState::Success(item.unwrap().clone()));
+        }
+        if let Some(blog) = parser.find_meta(&id).and_then(|meta| cached_blog(&meta)) {
+            log::debug!("Cached in storage: {}", ctx.props().title);
+            parser.dispatch(ParseAct::CacheBlog(blog.clone()));
+            ctx.link().send_message(Msg::Revalidate);
+            return Self::new(ctx, FetchState::Success(blog));
         }
         let cnt = parser.inner().records.get(&id);
         if matches!(cnt, Some(x) if *x > 3) {
             log::debug!("Failed: {}", ctx.props().title);
-            return Self {
-                post: FetchState::Failed(FetchError {
-                    err: JsValue::from("Failed more than 3 times"),
-                }),
-                notified: false,
-            };
+            return Self::new(
+                ctx,
+                FetchState::Failed(FetchError::Parse("Failed more than 3 times".into())),
+            );
         }
         log::debug!("Not Fetcded: {}", ctx.props().title);
-        Self {
-            post: FetchState::NotFetching,
-            notified: false,
-        }
+        Self::new(ctx, FetchState::NotFetching)
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        // another post is routed to, eg: the translation
+        *self = Self::create(ctx);
+        true
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
@@ -125,9 +176,83 @@ impl Component for Post {
                 true
             }
             Msg::Cached => false,
+            Msg::Copy(button) => {
+                let code = button
+                    .closest(".code-block")
+                    .ok()
+                    .flatten()
+                    .and_then(|block| block.get_elements_by_tag_name("code").item(0))
+                    .and_then(|code| code.text_content())
+                    .unwrap_or_default();
+                ctx.link().send_future(async move {
+                    let copied = write_clipboard(&code)
+                        .await
+                        .map_err(|e| log::error!("code not copied: {:?}", e))
+                        .is_ok();
+                    Msg::Copied(button, copied)
+                });
+                false
+            }
+            Msg::Copied(button, copied) => {
+                let (config, _) = ctx
+                    .link()
+                    .context::<SiteConfigContext>(Callback::noop())
+                    .expect("Site Config Context not found");
+                let label = match copied {
+                    true => config.t("copied"),
+                    false => config.t("copy_failed"),
+                };
+                button.set_text_content(Some(label));
+                // the label is put back after a while
+                let label = config.t("copy");
+                let reset = Closure::once_into_js(move || button.set_text_content(Some(label)));
+                if let Some(window) = web_sys::window() {
+                    let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                        reset.unchecked_ref(),
+                        COPIED_MS,
+                    );
+                }
+                false
+            }
+            Msg::Zoom(image) => {
+                let (images, index) = content_images(&image);
+                if images.is_empty() {
+                    return false;
+                }
+                self.zoomed = Some((images, index));
+                true
+            }
+            Msg::Unzoom => self.zoomed.take().is_some(),
+            Msg::TranslationLoaded => true,
+            Msg::Revalidate => {
+                let (parser, _) = ctx
+                    .link()
+                    .context::<ParseActContext>(Callback::noop())
+                    .expect("Parser Context not found");
+                if let Some(meta) = parser.find_meta(&ctx.props().id) {
+                    let current = match self.post {
+                        FetchState::Success(ref blog) => Some(blog.clone()),
+                        _ => None,
+                    };
+                    ctx.link().send_future_batch(async move {
+                        match fetch_blog(&meta).await {
+                            Ok(blog) if Some(&blog) != current.as_ref() => {
+                                parser.dispatch(ParseAct::CacheBlog(blog.clone()));
+                                vec![Msg::SetMarkdownFetchState(FetchState::Success(blog))]
+                            }
+                            Ok(_) => vec![],
+                            Err(err) => {
+                                log::warn!("cached {:?} not revalidated: {}", meta.path, err);
+                                vec![]
+                            }
+                        }
+                    });
+                }
+                false
+            }
             Msg::SetMarkdownFetchState(state) => {
                 log::trace!("set state");
-                self.post = state;
+                self.set_post(ctx, state);
                 true
             }
             Msg::GetMarkdown => {
@@ -136,24 +261,23 @@ impl Component for Post {
                     .link()
                     .context::<ParseActContext>(Callback::noop())
                     .expect("Parser Context not found");
-                let meta = parser.get_meta(&ctx.props().id);
+                let meta = parser.find_meta(&ctx.props().id);
                 if meta.is_none() {
                     ctx.link()
-                        .send_message(Msg::SetMarkdownFetchState(FetchState::Failed(FetchError {
-                            err: "Blog Not Found".into(),
-                        })));
+                        .send_message(Msg::SetMarkdownFetchState(FetchState::Failed(
+                            FetchError::NotFound(ctx.props().title.replace("-", " ")),
+                        )));
                     return false;
                 }
-                let meta = meta.unwrap().clone();
+                let meta = meta.unwrap();
+                self.notified = false;
                 ctx.link().send_future(async move {
-                    match read_file(meta.path.to_str().unwrap()).await {
-                        Ok(md) => {
-                            let data = str2blog(&md, &meta).unwrap();
+                    match fetch_blog(&meta).await {
+                        Ok(data) => {
                             parser.dispatch(ParseAct::CacheBlog(data.clone()));
                             log::trace!("async load {}, len: {}", data.meta.title, parser.len());
                             Msg::SetMarkdownFetchState(FetchState::Success(data))
                         }
-
                         Err(err) => Msg::SetMarkdownFetchState(FetchState::Failed(err)),
                     }
                 });
@@ -164,8 +288,50 @@ impl Component for Post {
         }
     }
 
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        if let FetchState::Success(ref blog) = self.post {
+            let (config, _) = ctx
+                .link()
+                .context::<SiteConfigContext>(Callback::noop())
+                .expect("Site Config Context not found");
+            i18n::set_document_lang(&blog.lang_or(&config.language));
+            head::set_post(&config, blog);
+            if self.reading.is_none() {
+                let path = Route::Post {
+                    id: ctx.props().id,
+                    title: ctx.props().title.clone(),
+                }
+                .to_path();
+                self.reading = Some(ReadTracker::new(&path));
+            }
+            // translations are known only in front matter
+            if !blog.translation.is_empty() && !self.translations_requested {
+                self.translations_requested = true;
+                let (parser, _) = ctx
+                    .link()
+                    .context::<ParseActContext>(Callback::noop())
+                    .expect("Parser Context not found");
+                fetch_all(ctx.link(), &parser, || Msg::TranslationLoaded);
+            }
+        }
+        // the fragment is found once the content is rendered
+        scroll::settle();
+    }
+
+    fn destroy(&mut self, ctx: &Context<Self>) {
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        i18n::set_document_lang(&config.language);
+    }
+
     fn view(&self, _ctx: &Context<Self>) -> Html {
         let onclick = _ctx.link().callback(|_| Msg::Notified);
+        let (config, _) = _ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
         match &self.post {
             FetchState::NotFetching => {
                 _ctx.link().send_message(Msg::GetMarkdown);
@@ -175,7 +341,7 @@ impl Component for Post {
                            <div class="container mt-24 notification is-light">
                                <button class="delete" {onclick}> </button>
                                <br />
-                               <strong class="is-large">{ " Blogs Not Fetched" }</strong>
+                               <strong class="is-large">{ config.t("blogs_not_fetched") }</strong>
                                <br />
                            </div>
                         }
@@ -186,10 +352,40 @@ impl Component for Post {
             }
             FetchState::Fetching => html! {},
             FetchState::Success(blog) => {
+                // the buttons and images are in the html of content,
+                // so the clicks on them are handled here
+                let oncontent = _ctx.link().batch_callback(|e: MouseEvent| {
+                    let target = e.target_dyn_into::<Element>()?;
+                    if let Some(button) = target
+                        .closest(&format!(".{}", markdown::COPY_BUTTON))
+                        .ok()?
+                    {
+                        return Some(Msg::Copy(button));
+                    }
+                    // the images in links follow them
+                    let zoomable = target.tag_name().eq_ignore_ascii_case("img")
+                        && target.closest(&format!(".{}", CONTENT)).ok()?.is_some()
+                        && target.closest("a").ok()?.is_none();
+                    match zoomable {
+                        true => Some(Msg::Zoom(target)),
+                        false => None,
+                    }
+                });
+                let lightbox = match &self.zoomed {
+                    Some((images, index)) => html! {
+                        <Lightbox images={images.clone()} index={*index}
+                            onclose={_ctx.link().callback(|_| Msg::Unzoom)} />
+                    },
+                    None => html! {},
+                };
                 let keywords = blog
                     .tags
                     .iter()
                     .map(|tag| html! { <span class="tag is-info">{ tag }</span> });
+                let authors = blog.authors_or(&config.admin);
+                let author_boxes = authors
+                    .iter()
+                    .map(|name| html! { <AuthorBox name={name.clone()} /> });
                 html! {
                     <>
                         <section class="hero is-medium is-light has-background">
@@ -199,65 +395,120 @@ impl Component for Post {
                                     <h1 class="title">
                                         { &blog.meta.title.replace("-", " ") }
                                     </h1>
+                                    <p class="subtitle is-6">
+                                        <Byline authors={authors.clone()} />
+                                    </p>
                                     <div class="tags">
                                         { for keywords }
                                     </div>
+                                    { self.view_translations(_ctx, blog) }
                                 </div>
                             </div>
                         </section>
-                        <div class="section container">
-                            { self.view_content() }
+                        <div class="section container" onclick={oncontent}>
+                            <div class={CONTENT}>
+                                { self.content.clone() }
+                            </div>
+                            { for author_boxes }
+                            <Comments meta={blog.meta.clone()} />
                         </div>
+                        { lightbox }
                     </>
                 }
             }
-            FetchState::Failed(err) => html! {
-                { if !self.notified {
-                   html!{
-                    <div class="container mt-24 notification is-danger">
-                        <button class="delete" {onclick}> </button>
-                    <strong class="is-large">{ " Blog Not Loaded Due To:" }</strong>
-                    <br />
-                   <p class="center"> { err.err.as_string().unwrap_or("Unexpected Error".into()) }</p>
-                    </div>
-                   } } else { html!{} }
+            FetchState::Failed(err) => {
+                let retry = _ctx.link().callback(|_| Msg::GetMarkdown);
+                html! {
+                    { if !self.notified {
+                       html!{
+                        <div class="container mt-24 notification is-danger">
+                            <button class="delete" {onclick}> </button>
+                        <strong class="is-large">{ config.t("blog_not_loaded") }</strong>
+                        <br />
+                       <p class="center"> { err.to_string() }</p>
+                       { if matches!(err, FetchError::NotFound(_)) { html!{} } else { html!{
+                           <button class="button is-light mt-3" onclick={retry}>{ config.t("retry") }</button>
+                       } } }
+                        </div>
+                       } } else { html!{} }
+                    }
                 }
-            },
+            }
         }
     }
 }
 impl Post {
-    fn view_content(&self) -> Html {
-        let dom_parser = web_sys::DomParser::new().unwrap();
+    fn new(ctx: &Context<Self>, post: FetchState<Blog>) -> Self {
+        let mut this = Self {
+            post: FetchState::NotFetching,
+            notified: false,
+            translations_requested: false,
+            reading: None,
+            zoomed: None,
+            content: html! {},
+        };
+        this.set_post(ctx, post);
+        this
+    }
+
+    /// show the post in state, the content is built if it is fetched
+    fn set_post(&mut self, ctx: &Context<Self>, post: FetchState<Blog>) {
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        self.post = post;
+        self.content = self.view_content(&config);
+    }
+
+    /// links to the translations of the blog
+    fn view_translations(&self, ctx: &Context<Self>, blog: &Blog) -> Html {
+        let (parser, _) = ctx
+            .link()
+            .context::<ParseActContext>(Callback::noop())
+            .expect("Parser Context not found");
+        let (config, _) = ctx
+            .link()
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        let translations = parser.translations_of(blog);
+        if translations.is_empty() {
+            return html! {};
+        }
+        let links = translations.iter().map(|e| {
+            let lang = e.lang_or(&config.language);
+            html! {
+                <Link<Route> classes={classes!("tag", "is-light")} to={Route::Post { id: e.meta.id, title: e.meta.title.clone() }}>
+                    { i18n::language_name(&lang) }
+                </Link<Route>>
+            }
+        });
+        html! {
+            <div class="tags translations">
+                <span class="tag is-white">{ config.t("translations") }</span>
+                { for links }
+            </div>
+        }
+    }
+
+    /// the content of blog, sanitized unless it is trusted in `site.toml`
+    fn view_content(&self, config: &SiteConfig) -> Html {
         let mut parts = Vec::new();
         if let FetchState::Success(ref blog) = self.post {
-            use pulldown_cmark::Options;
-            let mut options = Options::empty();
-            options.insert(Options::ENABLE_HEADING_ATTRIBUTES);
-            options.insert(Options::ENABLE_SMART_PUNCTUATION);
-            options.insert(Options::ENABLE_TABLES);
-            options.insert(Options::ENABLE_FOOTNOTES);
-            options.insert(Options::ENABLE_STRIKETHROUGH);
-            options.insert(Options::ENABLE_TASKLISTS);
             blog.content.iter().for_each(|part| {
-                let parser = pulldown_cmark::Parser::new_ext(&part, options);
-                let mut output = String::with_capacity(part.len() * 3 / 2);
-                pulldown_cmark::html::push_html(&mut output, parser);
-                let output_div = format!(
-                    "<div class = \"markdown-body\"> {} </div>",
-                    output //.replace("<p>", "\n").replace("</p>", "<br/>")
-                );
+                let html = match config.sanitize.trusts(&blog.meta.path) {
+                    true => markdown::to_html(part),
+                    false => markdown::sanitize(&markdown::to_html(part), &config.sanitize),
+                };
+                let output_div = format!("<div class = \"markdown-body\"> {} </div>", html);
                 log::debug!("{}", &format!("parsing markdown into html"));
-                if let Ok(element) =
-                    dom_parser.parse_from_string(&output_div, web_sys::SupportedType::TextHtml)
-                {
+                let nodes = markdown::to_vnodes_with(&output_div, |body| {
+                    markdown::add_copy_buttons(body, config.t("copy"));
+                    scroll::link_fragments(body);
+                });
+                if let Some(nodes) = nodes {
                     log::debug!("{}", &format!("Done: parsing markdown into html"));
-                    let eles = element.body().unwrap().children();
-                    for ind in 0..eles.length() {
-                        let node = eles.get_with_index(ind).unwrap();
-                        let vnode = Html::VRef(node.into());
-                        parts.push(vnode);
-                    }
+                    parts.extend(nodes);
                 } else {
                     log::debug!("{}", &format!("failed to parsed markdown into html"));
                     let node = html! { <p> {"the markdown file is not parsed"} </p> };
@@ -1,27 +1,49 @@
 use crate::components::pagination::PageQuery;
 use crate::components::pagination::Pagination;
 use crate::components::post_card::BlogCard;
-use crate::constant::ITEMS_PER_PAGE;
+use crate::head;
 use crate::parser::ParseAct;
+use crate::scroll;
 use crate::ParseActContext;
 use crate::Route;
+use crate::SiteConfigContext;
+use wasm_bindgen::{prelude::Closure, JsCast};
+use web_sys::{Element, IntersectionObserver, IntersectionObserverEntry};
+use yew::context::ContextHandle;
 use yew::prelude::*;
 use yew_router::prelude::*;
 
 pub enum Msg {
     PageUpdated,
     LoadPageUpdate,
+    ParserUpdated(ParseActContext),
 }
 
 pub struct PostList {
+    /// the page in query, the first one displayed
     page: u64,
+    /// the last page displayed, more pages are appended
+    /// to the first one in infinite scroll
+    last: u64,
+    parser: ParseActContext,
+    _parser_listener: ContextHandle<ParseActContext>,
+    /// the element at the bottom of list, the next page is loaded
+    /// once it is scrolled into view
+    sentinel: NodeRef,
+    observer: Option<(IntersectionObserver, Closure<dyn FnMut(js_sys::Array)>)>,
     _listener: LocationHandle,
 }
 
-fn current_page(ctx: &Context<PostList>) -> u64 {
+/// the page in query, it is within the pages of all posts
+fn current_page(ctx: &Context<PostList>, total_pages: u64) -> u64 {
     let location = ctx.link().location().unwrap();
 
-    location.query::<PageQuery>().map(|it| it.page).unwrap_or(1)
+    location
+        .query::<PageQuery>()
+        .map(|it| it.page)
+        .unwrap_or(1)
+        .max(1)
+        .min(total_pages)
 }
 
 impl Component for PostList {
@@ -34,46 +56,58 @@ impl Component for PostList {
             .link()
             .add_location_listener(link.callback(move |_| Msg::PageUpdated))
             .unwrap();
+        let (parser, parser_listener) = ctx
+            .link()
+            .context::<ParseActContext>(ctx.link().callback(Msg::ParserUpdated))
+            .expect("Parser Context not found");
+        let page = current_page(ctx, parser.total_page());
+        parser.dispatch(ParseAct::LoadPages(page));
 
         Self {
-            page: current_page(ctx),
+            page,
+            last: page,
+            parser,
+            _parser_listener: parser_listener,
+            sentinel: NodeRef::default(),
+            observer: None,
             _listener: listener,
         }
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
-            Msg::PageUpdated => self.page = current_page(ctx),
-            Msg::LoadPageUpdate => self.page += 1,
+            Msg::PageUpdated => {
+                self.page = current_page(ctx, self.parser.total_page());
+                self.last = self.page;
+                self.parser.dispatch(ParseAct::LoadPages(self.last));
+            }
+            Msg::LoadPageUpdate => {
+                if self.last >= self.parser.total_page() {
+                    return false;
+                }
+                self.last += 1;
+                self.parser.dispatch(ParseAct::LoadPages(self.last));
+            }
+            Msg::ParserUpdated(parser) => self.parser = parser,
         }
         true
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
         let page = self.page;
-
-        let (parser, _) = ctx
+        let total_pages = self.parser.total_page();
+        let (config, _) = ctx
             .link()
-            .context::<ParseActContext>(Callback::noop())
-            .expect("Parser Context not found");
-        let parse = parser.clone();
-        let link = ctx.link().clone();
-        let all_pages = parser.all_page();
-        let total_pages = parser.total_page();
-        let onclick = Callback::from(move |_| {
-            parser.dispatch(ParseAct::MoreBlogMeta);
-            link.send_message(Msg::LoadPageUpdate);
-        });
-        let view = if &parse.display == "gridCard" {
-            "List View"
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        let view = if &self.parser.display == "gridCard" {
+            config.t("list_view")
         } else {
-            "Grid View"
+            config.t("grid_view")
         };
-        let link = ctx.link().clone();
-        let changemode = Callback::from(move |_| {
-            parse.dispatch(ParseAct::ChangeDisplayMode);
-            link.send_message(Msg::PageUpdated);
-        });
+        let parse = self.parser.clone();
+        let changemode = Callback::from(move |_| parse.dispatch(ParseAct::ChangeDisplayMode));
+        let onclick = ctx.link().callback(|_| Msg::LoadPageUpdate);
 
         html! {
             <>
@@ -81,19 +115,24 @@ impl Component for PostList {
                 { view }
             </button>
             <div class="section container">
-                { self.view_posts(ctx) }
-                <Pagination
-                    {page}
-                    total_pages={total_pages}
-                    route_to_page={Route::Home}
-                />
+                { self.view_posts() }
             {
-                if total_pages == page && all_pages > page {
+                if !config.infinite_scroll() {
+                    html!{
+                        <Pagination
+                            {page}
+                            total_pages={total_pages}
+                            route_to_page={Route::Home}
+                        />
+                    }
+                } else if self.last < total_pages {
                     html!{
-                        <>
-                        <button class= "button is-medium is-fullwidth is-inverted is-responsive" {onclick}> { "Load More Page" }  </button>
-                        </>
+                        <div ref={self.sentinel.clone()}>
+                            <button class= "button is-medium is-fullwidth is-inverted is-responsive" {onclick}> { config.t("load_more_page") }  </button>
+                        </div>
                     }
+                } else if page == 0 {
+                    html!{ { config.t("no_page_found") } }
                 } else {
                     html!{}
                 }
@@ -102,42 +141,83 @@ impl Component for PostList {
             </>
         }
     }
-}
-impl PostList {
-    fn view_posts(&self, _ctx: &Context<Self>) -> Html {
-        let (parse, _) = _ctx
+
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        let (config, _) = ctx
             .link()
-            .context::<ParseActContext>(Callback::noop())
-            .expect("Parser Context not found");
-        let start_seed = (self.page - 1) * ITEMS_PER_PAGE;
-        let mut cards = Vec::new();
-        let display = &parse.display;
-        for ind in 0..ITEMS_PER_PAGE {
-            let index = ind + start_seed;
-            if index as usize > parse.ids.len() - 1 || parse.ids.is_empty() {
-                break;
+            .context::<SiteConfigContext>(Callback::noop())
+            .expect("Site Config Context not found");
+        let route = match self.page {
+            0 | 1 => "".to_string(),
+            page => format!("?page={}", page),
+        };
+        head::set_page(&config, "", "", &route);
+        scroll::settle();
+        if !config.infinite_scroll() {
+            return;
+        }
+        if self.observer.is_none() {
+            let link = ctx.link().clone();
+            let callback = Closure::wrap(Box::new(move |entries: js_sys::Array| {
+                let visible = entries.iter().any(|e| {
+                    e.unchecked_into::<IntersectionObserverEntry>()
+                        .is_intersecting()
+                });
+                if visible {
+                    link.send_message(Msg::LoadPageUpdate);
+                }
+            }) as Box<dyn FnMut(js_sys::Array)>);
+            match IntersectionObserver::new(callback.as_ref().unchecked_ref()) {
+                Ok(observer) => self.observer = Some((observer, callback)),
+                // the button is left to load more
+                Err(e) => log::error!("IntersectionObserver not created: {:?}", e),
             }
-            let id = parse.ids[index as usize];
-            let meta = parse.get_meta(&id).unwrap();
-            let item = html! {
-                <li class="list-item mb-1">
-                    <BlogCard id={id} title={meta.title.clone() } display={display.clone()}/>
-                </li>
-            };
-            cards.push(item);
         }
+        if let (Some((observer, _)), Some(sentinel)) =
+            (&self.observer, self.sentinel.cast::<Element>())
+        {
+            // observe it again to be notified even if it is still in view,
+            // eg: the loaded page does not fill the screen
+            observer.unobserve(&sentinel);
+            observer.observe(&sentinel);
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &Context<Self>) {
+        if let Some((observer, _)) = self.observer.take() {
+            observer.disconnect();
+        }
+    }
+}
+impl PostList {
+    fn view_posts(&self) -> Html {
+        let parse = &self.parser;
+        let display = &parse.display;
+        let cards = parse
+            .page_ids(self.page, self.last)
+            .iter()
+            .filter_map(|id| parse.get_meta(id))
+            .map(|meta| {
+                html! {
+                    <li class="list-item mb-1">
+                        <BlogCard id={meta.id} title={meta.title.clone() } display={display.clone()}/>
+                    </li>
+                }
+            })
+            .collect::<Vec<_>>();
+        let per_column = (cards.len() + 2) / 3;
         let mut cards = cards.into_iter();
         if display == "gridCard" {
             html! {
                 <div class="columns">
                     <div class="column">
                         <ul class="list">
-                            { for cards.by_ref().take(ITEMS_PER_PAGE as usize / 3) }
+                            { for cards.by_ref().take(per_column) }
                         </ul>
                     </div>
                     <div class="column">
                         <ul class="list">
-                            { for cards.by_ref().take(ITEMS_PER_PAGE as usize / 3) }
+                            { for cards.by_ref().take(per_column) }
                         </ul>
                     </div>
                     <div class="column">
@@ -2,155 +2,126 @@
 //! Here provide a module that accepts a markdown file to
 //! reproduce a struct `Post`
 //! make it static based blog
+pub use crate::common::front_matter::str2blog;
+use crate::common::page;
+pub use crate::common::page::Order;
 use crate::BlogMeta;
-use crate::{constant::ITEMS_PER_PAGE, content::Blog, pages::post::read_file};
+use crate::{
+    analytics, cache, config,
+    constant::ITEMS_PER_PAGE,
+    content::{manifest_authors, slugify, Blog},
+    fetch::{fetch_cached, FetchError},
+    pages::post::read_file,
+};
+use crate::{i18n, routing, ParseActContext};
 use std::collections::HashMap;
 use std::rc::Rc;
-use wasm_bindgen::JsCast;
+use yew::html::Scope;
 use yew::prelude::*;
 
 /// read the index of directory
-pub async fn read_dir() -> Vec<String> {
-    use crate::constant;
-    use wasm_bindgen_futures::JsFuture;
+pub async fn read_dir() -> Result<Vec<String>, FetchError> {
     log::trace!("{}", &format!("read dir",));
-    let dir = if constant::SUBPATH != "/" {
-        format!("{}{}", constant::SUBPATH, "posts/")
-    } else {
-        "posts/".into()
-    };
-    log::debug!("{}", &format!("parse dir: {:?}", dir));
-    let window = web_sys::window().unwrap();
-    let protocol = window
-        .location()
-        .protocol()
-        .expect("Protocol is NULL")
-        .to_string();
-    let host = window
-        .location()
-        .host()
-        .expect("HOST is not NULL")
-        .to_string();
-    let mut url = format!("{}//{}/{}", protocol, host, dir);
-    if !url.ends_with("/") {
-        url.push_str("/");
-    }
-    url.push_str("markdown.index");
-    log::debug!("{}", &format!("url: {:?}", url));
-    let res = JsFuture::from(window.fetch_with_str(&url)).await.unwrap();
-    assert!(res.is_instance_of::<web_sys::Response>());
-    let res_val = res.dyn_into::<web_sys::Response>().unwrap();
-    let text = JsFuture::from(res_val.text().unwrap())
-        .await
-        .unwrap()
-        .as_string()
-        .expect("the index shall return string");
+    let text = fetch_cached(&config::site_url("posts/markdown.index")).await?;
     log::trace!("{}", &format!("content {:?}", text));
-    text.split("\n")
+    Ok(text
+        .split("\n")
         .map(|e| e.trim().to_string())
         .filter(|e| e.len() != 0 && e.ends_with("rmd"))
-        .collect()
+        .collect())
 }
 
-pub fn str2blog(s: &str, meta: &BlogMeta) -> Option<Blog> {
-    log::trace!("parsing a string into a blog");
-    let sp = s.splitn(3, "---").collect::<Vec<_>>();
-    let metadata = sp[1].trim();
-    let cont = sp[2]
-        .splitn(2, "<!--break-->")
-        .map(|e| e.trim().to_owned())
-        .collect::<Vec<String>>();
-    let pat = regex::Regex::new(r"(?P<key>\w+)\s*:\s*(?P<value>.*?$)").unwrap();
-    let pat_tag = regex::Regex::new(r"\s*-\s+(?P<tag>.*?$)").unwrap();
-    let pat_ml = regex::Regex::new(r"(\s{2,}||\t{1,})(?P<ln>.*?$)").unwrap();
-    let mut map = HashMap::new();
-    let mut tags = Vec::new();
-    let mut lastkey = "";
-    for line in metadata.split("\n") {
-        if line.trim().len() < 1 {
-            // empty line skip it
-            lastkey = "";
-            continue;
-        }
-        if let Some(caps) = pat.captures(line) {
-            if caps.len() == 1 {
-                log::trace!("Not Matched: {}", line);
-                break;
-            }
-            let key = caps.name("key").map_or("", |e| e.as_str());
-            lastkey = key;
-            let value = caps.name("value").map_or("", |e| e.as_str());
-            if key == "tags" {
-                lastkey = key;
-            } else {
-                map.insert(key, value.trim().to_owned());
-            }
-            continue;
-        }
-        if let Some(caps) = pat_tag.captures(line) {
-            if let Some(tag) = caps.name("tag") {
-                let tag = tag.as_str().trim().to_owned();
-                tags.push(tag);
-                continue;
-            }
-        }
-        if let Some(caps) = pat_ml.captures(line) {
-            let ln = caps.name("ln").unwrap().as_str().trim();
-            if let Some(item) = map.get_mut(&lastkey) {
-                item.push_str(ln);
-            } else {
-                log::debug!("Line ingnored: {}", ln);
-            }
-        }
-    }
-    if map.get(&"title").is_none() {
-        log::error!("Title Missing for {:?}", meta.path);
-        return None;
+/// the authors of posts by id, from the manifest generated by `noer build`
+pub async fn load_authors() -> Result<HashMap<u64, Vec<String>>, FetchError> {
+    let url = config::site_url("posts/manifest.json");
+    let text = fetch_cached(&url).await?;
+    if text.trim_start().starts_with('<') {
+        // the page served in place of missing file, eg: by `trunk serve`
+        return Err(FetchError::NotFound(url));
     }
-    if map.get(&"published").is_none() {
-        log::error!("Attribute `published` Missing for {:?}", meta.path);
-        return None;
-    }
-    let mut blog = Blog {
-        meta: meta.clone(),
-        tags,
-        content: cont,
-        published: map
-            .remove("published")
-            .unwrap_or("false".into())
-            .parse::<bool>()
-            .unwrap(),
-        ignored: false,
-    };
-    match map.remove("date") {
-        Some(s) => blog.date_info(Some(&s)),
-        None => blog.date_info(None),
+    manifest_authors(&text).map_err(FetchError::Parse)
+}
+
+/// fetch the file of meta and parse it into blog
+pub async fn fetch_blog(meta: &BlogMeta) -> Result<Blog, FetchError> {
+    let path = meta.path.to_str().unwrap();
+    let md = read_file(path).await?;
+    let mut blog = str2blog(&md, meta)
+        .ok_or_else(|| FetchError::Parse(format!("{:?} is not a valid blog", meta.path)))?;
+    let url = config::site_url(path);
+    // keep the hero picked last time, so the blog is unchanged
+    // if the file is not modified
+    if let Some(cached) = cache::get_blog(&url) {
+        blog.meta.hero = cached.meta.hero;
     }
-    blog.meta.get_hash();
-    println!("blog path: {:?}", blog.meta.path);
-    Some(blog)
+    cache::put_blog(&url, &blog);
+    Ok(blog)
 }
 
-/// the order of post sort
-#[derive(PartialEq, Clone, Debug)]
-pub enum Order {
-    Dec,
-    Inc,
-    Hot,
+/// the blog of meta parsed last time, it may be stale
+pub fn cached_blog(meta: &BlogMeta) -> Option<Blog> {
+    cache::get_blog(&config::site_url(meta.path.to_str()?))
 }
 
-impl Default for Order {
-    fn default() -> Self {
-        Self::Dec
+/// fetch all blogs not cached yet, since some infomation is only
+/// known in front matter, eg: authors, language
+/// `done` is sent to the component once each is fetched,
+/// returns the number of blogs fetching
+pub fn fetch_all<COMP, F>(link: &Scope<COMP>, parser: &ParseActContext, done: F) -> usize
+where
+    COMP: Component,
+    F: Fn() -> COMP::Message + Clone + 'static,
+{
+    fetch_where(link, parser, |_| true, done)
+}
+
+/// same as `fetch_all`, only the blogs that `keep` are fetched,
+/// eg: the ones whose authors in the manifest match
+pub fn fetch_where<COMP, K, F>(
+    link: &Scope<COMP>,
+    parser: &ParseActContext,
+    keep: K,
+    done: F,
+) -> usize
+where
+    COMP: Component,
+    K: Fn(&BlogMeta) -> bool,
+    F: Fn() -> COMP::Message + Clone + 'static,
+{
+    parser.dispatch(ParseAct::AllBlogMeta);
+    let metas = parser
+        .paths
+        .iter()
+        .filter_map(|path| BlogMeta::with_path(path))
+        .filter(|meta| !parser.inner().blogs.contains_key(&meta.id) && keep(meta))
+        .collect::<Vec<_>>();
+    let pending = metas.len();
+    for meta in metas {
+        let parser = parser.clone();
+        let done = done.clone();
+        link.send_future(async move {
+            match fetch_blog(&meta).await {
+                Ok(blog) => parser.dispatch(ParseAct::CacheBlog(blog)),
+                Err(e) => log::error!("blog {:?} not loaded: {}", meta.path, e),
+            }
+            done()
+        });
     }
+    pending
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum ParseAct {
     CacheBlog(Blog),
     MoreBlogMeta,
+    AllBlogMeta,
+    /// load the metas of the first pages
+    LoadPages(u64),
     BlogPath(Vec<String>),
     ChangeDisplayMode,
+    ItemsPerPage(u64),
+    /// the authors of posts by id, see `load_authors`
+    Authors(HashMap<u64, Vec<String>>),
 }
 
 /// the inner data of Parser
@@ -182,6 +153,9 @@ pub struct Parser {
     pub order: Order,
     pub parsed: bool,
     pub display: String,
+    pub per_page: u64,
+    /// the authors of posts by id in the manifest, empty if it is not built
+    pub authors: HashMap<u64, Vec<String>>,
 }
 
 impl Reducible for Parser {
@@ -189,13 +163,32 @@ impl Reducible for Parser {
     fn reduce(mut self: Rc<Self>, action: Self::Action) -> Rc<Self> {
         match action {
             ParseAct::CacheBlog(blog) => (*Rc::make_mut(&mut self)).insert(blog.meta.id, blog),
-            ParseAct::MoreBlogMeta => (*Rc::make_mut(&mut self)).load_meta(ITEMS_PER_PAGE),
-            ParseAct::BlogPath(paths) => (Rc::make_mut(&mut self)).paths = paths,
+            ParseAct::MoreBlogMeta => {
+                let cnt = self.per_page;
+                (*Rc::make_mut(&mut self)).load_meta(cnt)
+            }
+            ParseAct::LoadPages(pages) => {
+                let cnt = (pages * self.per_page).saturating_sub(self.ids.len() as u64);
+                (*Rc::make_mut(&mut self)).load_meta(cnt)
+            }
+            ParseAct::AllBlogMeta => {
+                let cnt = self.paths.len().saturating_sub(self.offset) as u64;
+                (*Rc::make_mut(&mut self)).load_meta(cnt)
+            }
+            ParseAct::BlogPath(paths) => {
+                // the invalid ones are dropped to count the pages exactly
+                (Rc::make_mut(&mut self)).paths = paths
+                    .into_iter()
+                    .filter(|path| BlogMeta::from_path(path).is_some())
+                    .collect()
+            }
             ParseAct::ChangeDisplayMode => match &self.display as &str {
                 "gridCard" => (Rc::make_mut(&mut self)).display = "listTile".into(),
                 "listTile" => (Rc::make_mut(&mut self)).display = "gridCard".into(),
                 _ => {}
             },
+            ParseAct::ItemsPerPage(cnt) => (Rc::make_mut(&mut self)).per_page = cnt,
+            ParseAct::Authors(authors) => (Rc::make_mut(&mut self)).authors = authors,
         }
         self
     }
@@ -218,6 +211,8 @@ impl Parser {
             order: Order::Dec,
             parsed: false,
             display: "listTile".into(),
+            per_page: ITEMS_PER_PAGE,
+            authors: HashMap::new(),
         }
     }
 
@@ -234,18 +229,10 @@ impl Parser {
 
     ///sort the blog with the Order
     pub fn order(&mut self) {
-        let cmp_fn = match self.order {
-            Order::Inc => |a: &(u64, u64), b: &(u64, u64)| -> std::cmp::Ordering {
-                a.0.partial_cmp(&b.0).unwrap()
-            },
-            Order::Dec => |a: &(u64, u64), b: &(u64, u64)| -> std::cmp::Ordering {
-                b.0.partial_cmp(&a.0).unwrap()
-            },
-            Order::Hot => |a: &(u64, u64), b: &(u64, u64)| -> std::cmp::Ordering {
-                b.0.partial_cmp(&a.0).unwrap()
-            },
-        };
-        self.inner.indexs.sort_by(cmp_fn);
+        match self.order {
+            Order::Hot => page::sort_hot(&mut self.inner.indexs, &analytics::post_scores()),
+            _ => page::sort(&mut self.inner.indexs, &self.order),
+        }
     }
 
     /// get the length of the blogs
@@ -253,25 +240,35 @@ impl Parser {
         self.inner.len
     }
 
-    pub fn all_page(&self) -> u64 {
-        let len = self.paths.len() as u64;
-        let num = len / ITEMS_PER_PAGE;
-        let remainder = len - ITEMS_PER_PAGE * num;
-        match remainder as u64 {
-            0 => num,
-            _ => num + 1,
-        }
+    /// the number of pages of all posts, loaded or not
+    pub fn total_page(&self) -> u64 {
+        page::total_pages(self.paths.len() as u64, self.per_page)
     }
 
-    /// all availible paths that can made into pages
-    pub fn total_page(&self) -> u64 {
-        let len = self.ids.len() as u64;
-        let num = len / ITEMS_PER_PAGE;
-        let remainder = len - ITEMS_PER_PAGE * num;
-        match remainder as u64 {
-            0 => num,
-            _ => num + 1,
+    /// the ids of blogs on the pages from `first` to `last`,
+    /// only the loaded ones are returned, see `ParseAct::LoadPages`
+    pub fn page_ids(&self, first: u64, last: u64) -> &[u64] {
+        &self.ids[page::page_range(first, last, self.per_page, self.ids.len())]
+    }
+
+    /// the authors of the post if they are known without fetching it,
+    /// from the blog loaded or cached, or the manifest
+    pub fn authors_of(&self, meta: &BlogMeta, default_author: &str) -> Option<Vec<String>> {
+        if let Some(blog) = self
+            .inner
+            .blogs
+            .get(&meta.id)
+            .cloned()
+            .or_else(|| cached_blog(meta))
+        {
+            return Some(blog.authors_or(default_author));
         }
+        self.authors
+            .get(&meta.id)
+            .map(|authors| match authors.is_empty() {
+                true => vec![default_author.to_string()],
+                false => authors.clone(),
+            })
     }
 
     /// get BlogMeta by path
@@ -279,6 +276,25 @@ impl Parser {
         self.metas.get(id)
     }
 
+    /// the metas of all posts, including the ones not loaded yet
+    pub fn all_metas(&self) -> Vec<BlogMeta> {
+        self.paths
+            .iter()
+            .filter_map(|path| BlogMeta::with_path(path))
+            .collect()
+    }
+
+    /// get BlogMeta by id, including the ones not loaded yet,
+    /// eg: the post is linked directly and not on the first page
+    pub fn find_meta(&self, id: &u64) -> Option<BlogMeta> {
+        self.metas.get(id).cloned().or_else(|| {
+            self.paths
+                .iter()
+                .filter_map(|path| BlogMeta::with_path(path))
+                .find(|meta| meta.id == *id)
+        })
+    }
+
     pub fn load_meta(&mut self, cnt: u64) {
         let mut delta = 0;
         for index in 0..cnt {
@@ -301,10 +317,68 @@ impl Parser {
 
     /// insert blog into inner
     pub fn insert(&mut self, key: u64, val: Blog) {
-        self.inner.indexs.push((key, val.meta.timestamp));
-        self.inner.len += 1;
         self.inner.inc(&key, 1);
-        self.inner.blogs.insert(key, val);
+        if self.inner.blogs.insert(key, val.clone()).is_none() {
+            self.inner.indexs.push((key, val.meta.timestamp));
+            self.inner.len += 1;
+        }
+    }
+
+    /// the other blogs that share the translation key with `blog`
+    pub fn translations_of(&self, blog: &Blog) -> Vec<&Blog> {
+        if blog.translation.is_empty() {
+            return vec![];
+        }
+        let mut blogs = self
+            .inner
+            .blogs
+            .values()
+            .filter(|e| e.translation == blog.translation && e.meta.id != blog.meta.id)
+            .collect::<Vec<_>>();
+        blogs.sort_by(|a, b| a.lang.cmp(&b.lang));
+        blogs
+    }
+
+    /// the blog that has the path as an alias
+    pub fn find_alias(&self, path: &str) -> Option<&Blog> {
+        let path = routing::normalize_route(path);
+        self.inner.blogs.values().find(|blog| {
+            blog.aliases
+                .iter()
+                .any(|alias| routing::alias_route(alias) == path)
+        })
+    }
+
+    /// all blogs in the language,
+    /// the blogs without `lang` are in `default_lang`
+    pub fn blogs_in(&self, lang: &str, default_lang: &str) -> Vec<&Blog> {
+        let code = i18n::primary(lang);
+        let mut blogs = self
+            .inner
+            .blogs
+            .values()
+            .filter(|blog| i18n::primary(&blog.lang_or(default_lang)) == code)
+            .collect::<Vec<_>>();
+        blogs.sort_by(|a, b| b.meta.timestamp.cmp(&a.meta.timestamp));
+        blogs
+    }
+
+    /// all blogs written by the author,
+    /// the blogs without `authors` are written by `default_author`
+    pub fn blogs_by(&self, author: &str, default_author: &str) -> Vec<&Blog> {
+        let slug = slugify(author);
+        let mut blogs = self
+            .inner
+            .blogs
+            .values()
+            .filter(|blog| {
+                blog.authors_or(default_author)
+                    .iter()
+                    .any(|e| slugify(e) == slug)
+            })
+            .collect::<Vec<_>>();
+        blogs.sort_by(|a, b| b.meta.timestamp.cmp(&a.meta.timestamp));
+        blogs
     }
 
     pub fn inner(&self) -> &InnerParser {
@@ -314,7 +388,7 @@ impl Parser {
     /// get the blog with the index
     pub fn get(&self, index: usize) -> Option<&Blog> {
         match self.inner.indexs.get(index) {
-            Some(path) => self.inner.blogs.get(&path.1),
+            Some(path) => self.inner.blogs.get(&path.0),
             None => None,
         }
     }
@@ -380,7 +454,7 @@ impl<'a> Iterator for Iter<'a> {
     type Item = &'a Blog;
     fn next(&mut self) -> Option<Self::Item> {
         if self.offset < self.len {
-            let (_, path) = self.index.get(self.offset).unwrap();
+            let (path, _) = self.index.get(self.offset).unwrap();
             self.offset += 1;
             return Some(self.data.get(path).unwrap());
         }
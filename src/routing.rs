@@ -0,0 +1,111 @@
+//! Here provide the restoring of path redirected by `404.html`
+//! and the redirects of aliases.
+//! static hosts serve `404.html` for the paths that are not files,
+//! eg: reloading `/noer/posts/123/title` on github pages,
+//! it redirects to the site with the original path in `redirect` query,
+//! which is put back into the url before the router starts
+use crate::common::url::Routing;
+use crate::config::{self, served_subpath};
+use crate::fetch::{fetch_cached, FetchError};
+use crate::BlogMeta;
+use crate::Route;
+
+/// the query key that `404.html` puts the original path in
+const REDIRECT_KEY: &str = "redirect";
+
+/// put the path redirected by `404.html` back into the url
+pub fn restore_redirect(routing: Routing) {
+    let window = match web_sys::window() {
+        Some(window) => window,
+        None => return,
+    };
+    let search = window.location().search().unwrap_or_default();
+    let path = match web_sys::UrlSearchParams::new_with_str(&search)
+        .ok()
+        .and_then(|e| e.get(REDIRECT_KEY))
+    {
+        Some(path) => path,
+        None => return,
+    };
+    let path = path.trim_start_matches('/');
+    let base = match served_subpath().as_str() {
+        "/" => "/".to_string(),
+        subpath => format!("/{}", subpath),
+    };
+    let url = routing.url(&base, path);
+    log::debug!("restore redirected path: {}", url);
+    if let Ok(history) = window.history() {
+        if let Err(e) = history.replace_state_with_url(&wasm_bindgen::JsValue::NULL, "", Some(&url))
+        {
+            log::error!("redirected path not restored: {:?}", e);
+        }
+    }
+}
+
+/// the path of route without the sub-path, `#` and slashes around,
+/// eg: "/noer/posts/123/title/" -> "posts/123/title"
+pub fn normalize_route(path: &str) -> String {
+    let path = path.trim().trim_start_matches('#').trim_matches('/');
+    let subpath = served_subpath();
+    let path = match subpath.as_str() {
+        "/" => path,
+        subpath => path
+            .strip_prefix(subpath.trim_end_matches('/'))
+            .map(|e| e.trim_start_matches('/'))
+            .unwrap_or(path),
+    };
+    path.trim_end_matches('/').to_string()
+}
+
+/// the path of route that an alias stands for,
+/// the old file of post is made into the route of it
+/// eg: "2021-10-10-old-title.rmd" -> "posts/<id>/old-title"
+pub fn alias_route(alias: &str) -> String {
+    let alias = alias.trim().trim_start_matches('/');
+    if alias.ends_with(".rmd") {
+        // the path of file as in `markdown.index`
+        let path = if alias.contains('/') {
+            alias.to_string()
+        } else {
+            format!("posts/{}", alias)
+        };
+        if let Some(meta) = BlogMeta::from_path(&path) {
+            return format!("posts/{}/{}", meta.id, meta.title);
+        }
+    }
+    normalize_route(alias)
+}
+
+/// the redirect table generated by `noer build` from the aliases,
+/// each line is `<alias> <path of post>`
+pub async fn load_redirects() -> Result<Vec<(String, String)>, FetchError> {
+    let url = config::site_url("posts/redirects.index");
+    let text = fetch_cached(&url).await?;
+    if text.trim_start().starts_with('<') {
+        // the page served in place of missing file, eg: by `trunk serve`
+        return Err(FetchError::NotFound(url));
+    }
+    Ok(text
+        .lines()
+        .filter_map(|line| {
+            let mut items = line.split_whitespace();
+            match (items.next(), items.next()) {
+                (Some(alias), Some(path)) => Some((alias_route(alias), path.to_string())),
+                _ => None,
+            }
+        })
+        .collect())
+}
+
+/// the route of post that the path is redirected to
+pub fn redirect_of(redirects: &[(String, String)], path: &str) -> Option<Route> {
+    let path = normalize_route(path);
+    redirects
+        .iter()
+        .find(|(alias, _)| *alias == path)
+        .and_then(|(_, post)| BlogMeta::from_path(post))
+        .map(|meta| Route::Post {
+            id: meta.id,
+            title: meta.title,
+        })
+}
@@ -0,0 +1,197 @@
+//! Here provide the scrolling on route changes, the pages are rendered
+//! asynchronously, so the browser can not restore the position itself.
+//! a new route is entered at the top, or at its fragment once the content
+//! is rendered, eg: `/posts/123/title#section`, and going back or forward
+//! returns to where the page was left
+use crate::common::url::Routing;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use wasm_bindgen::{prelude::Closure, JsCast};
+use web_sys::{Element, ScrollRestoration};
+
+/// how long the position is waited for in milliseconds, the content
+/// may not be long enough to scroll to until the posts are fetched
+const SETTLE_MS: f64 = 5000.0;
+
+/// where a route is entered at
+#[derive(Clone, Debug, PartialEq)]
+enum Target {
+    Top,
+    Position(f64),
+    /// the id of element, eg: a heading
+    Fragment(String),
+}
+
+thread_local! {
+    static ROUTING: Cell<Routing> = const { Cell::new(Routing::History) };
+    /// the route shown, eg: "/posts/123/title#section" or "/?page=2"
+    static CURRENT: RefCell<String> = const { RefCell::new(String::new()) };
+    /// the positions routes are left at
+    static POSITIONS: RefCell<HashMap<String, f64>> = RefCell::new(HashMap::new());
+    /// whether the route is changed by going back or forward
+    static POPPED: Cell<bool> = const { Cell::new(false) };
+    /// the target to scroll to once it is rendered, and its deadline
+    static PENDING: RefCell<Option<(Target, f64)>> = const { RefCell::new(None) };
+}
+
+/// the route in url and its fragment, the route of `HashRouter` is in the hash,
+/// eg: "/noer/#/posts/123/title#section" -> ("/posts/123/title", "section")
+fn location() -> Option<(String, String)> {
+    let location = web_sys::window()?.location();
+    let hash = location.hash().ok()?;
+    let (route, fragment) = match ROUTING.with(|e| e.get()) {
+        Routing::History => (
+            format!("{}{}", location.pathname().ok()?, location.search().ok()?),
+            hash.trim_start_matches('#').to_string(),
+        ),
+        Routing::Hash => {
+            let hash = hash.trim_start_matches('#');
+            match hash.split_once('#') {
+                Some((route, fragment)) => (route.to_string(), fragment.to_string()),
+                None => (hash.to_string(), "".into()),
+            }
+        }
+    };
+    let fragment = js_sys::decode_uri_component(&fragment)
+        .ok()
+        .and_then(|e| e.as_string())
+        .unwrap_or(fragment);
+    Some((route, fragment))
+}
+
+/// take over the scrolling from the browser, it is called once
+pub fn init(routing: Routing) {
+    ROUTING.with(|e| e.set(routing));
+    let window = match web_sys::window() {
+        Some(window) => window,
+        None => return,
+    };
+    if let Ok(history) = window.history() {
+        if let Err(e) = history.set_scroll_restoration(ScrollRestoration::Manual) {
+            log::error!("scroll restoration not set: {:?}", e);
+        }
+    }
+    let on_pop = Closure::wrap(Box::new(|| POPPED.with(|e| e.set(true))) as Box<dyn FnMut()>);
+    let on_scroll = Closure::wrap(Box::new(|| {
+        let y = match web_sys::window().and_then(|w| w.scroll_y().ok()) {
+            Some(y) => y,
+            None => return,
+        };
+        let current = CURRENT.with(|e| e.borrow().clone());
+        POSITIONS.with(|e| e.borrow_mut().insert(current, y));
+    }) as Box<dyn FnMut()>);
+    for (name, callback) in [("popstate", on_pop), ("scroll", on_scroll)] {
+        if let Err(e) =
+            window.add_event_listener_with_callback(name, callback.as_ref().unchecked_ref())
+        {
+            log::error!("{} not listened: {:?}", name, e);
+        }
+        // the listeners live as long as the app
+        callback.forget();
+    }
+}
+
+/// a route is rendered, where to scroll is decided if it is entered,
+/// it is scrolled to once the page is rendered, see `settle`
+pub fn entered() {
+    let (route, fragment) = match location() {
+        Some(location) => location,
+        None => return,
+    };
+    let key = match fragment.is_empty() {
+        true => route,
+        false => format!("{}#{}", route, fragment),
+    };
+    let popped = POPPED.with(|e| e.replace(false));
+    let changed = CURRENT.with(|e| {
+        let mut current = e.borrow_mut();
+        let changed = *current != key;
+        *current = key.clone();
+        changed
+    });
+    if !changed {
+        return;
+    }
+    let target = match POSITIONS.with(|e| e.borrow().get(&key).copied()) {
+        Some(y) if popped => Target::Position(y),
+        _ if !fragment.is_empty() => Target::Fragment(fragment),
+        _ => Target::Top,
+    };
+    log::debug!("scroll {:?} once {} is rendered", target, key);
+    PENDING.with(|e| *e.borrow_mut() = Some((target, js_sys::Date::now() + SETTLE_MS)));
+}
+
+/// scroll to the target of the route entered if the page is rendered enough,
+/// the pages call it once they are rendered
+pub fn settle() {
+    let (target, deadline) = match PENDING.with(|e| e.borrow().clone()) {
+        Some(pending) => pending,
+        None => return,
+    };
+    let window = match web_sys::window() {
+        Some(window) => window,
+        None => return,
+    };
+    let document = match window.document() {
+        Some(document) => document,
+        None => return,
+    };
+    let expired = js_sys::Date::now() > deadline;
+    let done = match &target {
+        Target::Top => {
+            window.scroll_to_with_x_and_y(0.0, 0.0);
+            true
+        }
+        Target::Position(y) => {
+            let height = document
+                .document_element()
+                .map(|e| e.scroll_height() as f64)
+                .unwrap_or(0.0);
+            let view = window
+                .inner_height()
+                .ok()
+                .and_then(|e| e.as_f64())
+                .unwrap_or(0.0);
+            // scroll as far as it is, and further once more is rendered
+            window.scroll_to_with_x_and_y(0.0, y.min((height - view).max(0.0)));
+            height - view >= *y
+        }
+        Target::Fragment(id) => match document.get_element_by_id(id) {
+            Some(element) => {
+                element.scroll_into_view();
+                true
+            }
+            None => false,
+        },
+    };
+    if done || expired {
+        PENDING.with(|e| *e.borrow_mut() = None);
+    }
+}
+
+/// the links to fragments in body keep the route shown, otherwise they are
+/// resolved against `<base href>` to the home page,
+/// eg: `href="#section"` -> `href="/noer/posts/123/title#section"`
+pub fn link_fragments(body: &Element) {
+    let (route, _) = match location() {
+        Some(location) => location,
+        None => return,
+    };
+    let route = match ROUTING.with(|e| e.get()) {
+        Routing::History => route,
+        Routing::Hash => format!("#{}", route),
+    };
+    let links = body.get_elements_by_tag_name("a");
+    for i in 0..links.length() {
+        let link = match links.item(i) {
+            Some(link) => link,
+            None => continue,
+        };
+        match link.get_attribute("href") {
+            Some(href) if href.starts_with('#') => {
+                let _ = link.set_attribute("href", &format!("{}{}", route, href));
+            }
+            _ => {}
+        }
+    }
+}